@@ -0,0 +1,83 @@
+/*
+ * Check the answers of the solutions, called through the C API, against solutions.txt:
+ *
+ *   cargo build --release
+ *   cc examples/c/check.c -Iinclude -Ltarget/release -ladventofcode2020 -o check
+ *   LD_LIBRARY_PATH=target/release ./check [INPUT_DIR]
+ *
+ * Days without an input and hashed answers are skipped. Exits with 1 if any answer is wrong.
+ */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "adventofcode2020.h"
+
+#define YEAR 2020
+
+static char *read_file(const char *path, size_t *len) {
+    FILE *f = fopen(path, "rb");
+    if (!f) return NULL;
+    size_t cap = 4096;
+    char *data = malloc(cap);
+    *len = 0;
+    size_t n;
+    while ((n = fread(data + *len, 1, cap - *len, f)) > 0) {
+        *len += n;
+        if (*len == cap) data = realloc(data, cap *= 2);
+    }
+    fclose(f);
+    return data;
+}
+
+int main(int argc, char **argv) {
+    const char *dir = argc > 1 ? argv[1] : "input";
+    char path[4096];
+    snprintf(path, sizeof path, "%s/solutions.txt", dir);
+    FILE *solutions = fopen(path, "r");
+    if (!solutions) {
+        fprintf(stderr, "cannot open %s\n", path);
+        return 2;
+    }
+
+    int failures = 0;
+    size_t days = aoc_days(YEAR);
+    char line[1024];
+    for (size_t d = 1; d <= days && fgets(line, sizeof line, solutions); d++) {
+        size_t len;
+        snprintf(path, sizeof path, "%s/%02zu.txt", dir, d);
+        char *input = read_file(path, &len);
+        if (!input) {
+            printf("y%04d d%02zu     no input\n", YEAR, d);
+            continue;
+        }
+        aoc_day *day = aoc_parse(YEAR, d, input, len);
+        free(input);
+        if (!day) {
+            printf("y%04d d%02zu     cannot parse the input\n", YEAR, d);
+            failures++;
+            continue;
+        }
+
+        char *expected = strtok(line, " \t\r\n");
+        for (size_t p = 1; p <= aoc_parts(day) && expected; p++, expected = strtok(NULL, " \t\r\n")) {
+            char answer[32];
+            const char *status;
+            if (strncmp(expected, "hash:", 5) == 0) {
+                status = "skipped (hashed answer)";
+            } else if (aoc_solve(day, p, answer, sizeof answer) < 0) {
+                status = "FAILED";
+                failures++;
+            } else if (strcmp(answer, expected) != 0) {
+                status = "WRONG";
+                failures++;
+            } else {
+                status = "ok";
+            }
+            printf("y%04d d%02zu p%02zu %s\n", YEAR, d, p, status);
+        }
+        aoc_free(day);
+    }
+    fclose(solutions);
+    return failures ? 1 : 0;
+}
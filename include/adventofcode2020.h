@@ -0,0 +1,71 @@
+/* Generated from src/ffi.rs with `cargo run -- c-header`, do not edit. */
+
+#ifndef ADVENTOFCODE2020_H
+#define ADVENTOFCODE2020_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A parsed day, which may only be used and released on the thread that parsed it */
+typedef struct aoc_day aoc_day;
+
+/*
+ * Write the years with solutions into `years`, at most `len` of them.
+ * Returns the number of years, which may be more than `len`.
+ *
+ * # Safety
+ * `years` must point to `len` writable elements.
+ */
+size_t aoc_years(uint16_t *years, size_t len);
+
+/*
+ * The number of days with solutions in a year, 0 if the year has none.
+ */
+size_t aoc_days(uint16_t year);
+
+/*
+ * Parse the input of a day (counted from 1). The input is `len` bytes of UTF-8 text without a NUL terminator,
+ * normalised the same way as the inputs of the runner. Returns NULL if there is no such day, or the input is not
+ * UTF-8 or cannot be parsed. The day must be released with `aoc_free`.
+ * The day may only be used and released on the thread that parsed it, other threads must parse their own.
+ *
+ * # Safety
+ * `input` must point to `len` readable bytes.
+ */
+aoc_day *aoc_parse(uint16_t year, size_t day, const char *input, size_t len);
+
+/*
+ * The number of parts of a parsed day.
+ *
+ * # Safety
+ * `day` must be a day returned by `aoc_parse` on the same thread and not released yet.
+ */
+size_t aoc_parts(const aoc_day *day);
+
+/*
+ * Solve a part (counted from 1) of a parsed day and write the answer into `buf` as a NUL-terminated decimal number,
+ * truncated to `len` bytes. Returns the length of the whole answer without the NUL like snprintf,
+ * or -1 if there is no such part or solving it failed.
+ *
+ * # Safety
+ * `day` must be a day returned by `aoc_parse` on the same thread and not released yet, `buf` must point to `len` writable bytes.
+ */
+int aoc_solve(const aoc_day *day, size_t part, char *buf, size_t len);
+
+/*
+ * Release a parsed day, NULL is ignored.
+ *
+ * # Safety
+ * `day` must be NULL or a day returned by `aoc_parse` on the same thread and not released yet.
+ */
+void aoc_free(aoc_day *day);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
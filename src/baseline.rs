@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::DayResult;
+
+// Stored timings of an earlier run, keyed by year, day and part (part 0 is the parsing)
+pub type Baseline = HashMap<(u16, usize, usize), u128>;
+
+// One "YEAR DAY PART NANOS" line per timing, the lines that aren't are skipped with a warning
+fn parse_line(line: &str) -> Option<((u16, usize, usize), u128)> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    match fields[..] {
+        [year, day, part, nanos] => Some(((year.parse().ok()?, day.parse().ok()?, part.parse().ok()?), nanos.parse().ok()?)),
+        _ => None
+    }
+}
+
+pub fn load(from: &Path) -> Baseline {
+    let mut res = Baseline::new();
+    if !from.is_file() { return res }
+    let content = match fs::read_to_string(from) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("cannot read {}: {}", from.display(), e);
+            return res
+        }
+    };
+    for (i, line) in content.lines().enumerate() {
+        match parse_line(line) {
+            Some((key, nanos)) => { res.insert(key, nanos); },
+            None => eprintln!("{}:{}: ignoring bad line: {}", from.display(), i + 1, line)
+        }
+    }
+    res
+}
+
+pub fn save(to: &Path, year: u16, results: &[DayResult]) -> io::Result<()> {
+    // Keep the timings of the other years and the days that were not run this time
+    let mut baseline = load(to);
+    for r in results {
+        if let Some(nanos) = r.parse_nanos {
+            baseline.insert((year, r.day, 0), nanos);
+        }
+        for p in &r.parts {
+            baseline.insert((year, r.day, p.part), p.nanos);
+        }
+    }
+
+    let mut keys: Vec<_> = baseline.keys().collect();
+    keys.sort();
+    let mut out = String::new();
+    for key in keys {
+        out.push_str(&format!("{} {} {} {}\n", key.0, key.1, key.2, baseline[key]));
+    }
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(to, out)
+}
+
+// The results made up here are used by the tests of the report too
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::env;
+    use crate::{spoiler::Solution, PartResult};
+
+    pub fn result(day: usize, parse_nanos: u128, nanos: &[u128]) -> DayResult {
+        let parts = nanos.iter().enumerate().map(|(i, &nanos)| {
+            PartResult { part: i + 1, actual: 42, expected: Solution::parse("42").unwrap(), nanos, spans: Vec::new() }
+        }).collect();
+        DayResult { day, parse_nanos: Some(parse_nanos), parse_spans: Vec::new(), parts, cached: false }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = env::temp_dir().join(format!("aoc-baseline-test-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert!(load(&path).is_empty());
+
+        save(&path, 2020, &[result(1, 10, &[20, 30]), result(2, 40, &[50])]).unwrap();
+        save(&path, 2021, &[result(1, 60, &[70])]).unwrap();
+        // Saving a day again keeps the others
+        save(&path, 2020, &[result(2, 45, &[55])]).unwrap();
+        let baseline = load(&path);
+        let expected: Baseline = [((2020, 1, 0), 10), ((2020, 1, 1), 20), ((2020, 1, 2), 30), ((2020, 2, 0), 45), ((2020, 2, 1), 55),
+                                  ((2021, 1, 0), 60), ((2021, 1, 1), 70)].iter().copied().collect();
+        assert_eq!(baseline, expected);
+
+        fs::write(&path, "2020 1 0 10\ngarbage\n2020 1 1\n2020 x 1 5\n\n2020 1 2 30 7\n2020 2 1 55\n").unwrap();
+        assert_eq!(load(&path), [((2020, 1, 0), 10), ((2020, 2, 1), 55)].iter().copied().collect());
+        fs::remove_file(&path).unwrap();
+    }
+}
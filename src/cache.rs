@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use crate::hash;
+
+// Identifies the running binary: its version, size and modification time change with every build
+fn build_id() -> u64 {
+    let exe = env::current_exe().ok().and_then(|exe| fs::metadata(exe).ok());
+    let (size, mtime) = match exe {
+        Some(meta) => (meta.len(), meta.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_nanos()).unwrap_or(0)),
+        None => (0, 0)
+    };
+    hash::fnv1a(format!("{} {} {}", env!("CARGO_PKG_VERSION"), size, mtime).as_bytes())
+}
+
+// Answers (and the time it took to find them) keyed by year, day, part and the hash of the input,
+// only for the current build. The file has one "BUILD YEAR DAY PART INPUT ANSWER NANOS" line per answer.
+pub struct Cache {
+    path: PathBuf,
+    build: u64,
+    entries: HashMap<(u16, usize, usize, u64), (i64, u128)>,
+}
+
+impl Cache {
+    pub fn open(path: &Path) -> Cache {
+        let build = build_id();
+        let mut entries = HashMap::new();
+        if let Ok(content) = fs::read_to_string(path) {
+            for line in content.lines() {
+                let f: Vec<&str> = line.split_whitespace().collect();
+                if f.len() != 7 || u64::from_str_radix(f[0], 16) != Ok(build) { continue }
+                if let (Ok(year), Ok(day), Ok(part), Ok(input), Ok(answer), Ok(nanos)) =
+                    (f[1].parse(), f[2].parse(), f[3].parse(), u64::from_str_radix(f[4], 16), f[5].parse(), f[6].parse()) {
+                    entries.insert((year, day, part, input), (answer, nanos));
+                }
+            }
+        }
+        Cache { path: path.to_path_buf(), build, entries }
+    }
+
+    pub fn get(&self, year: u16, day: usize, part: usize, input: u64) -> Option<(i64, u128)> {
+        self.entries.get(&(year, day, part, input)).cloned()
+    }
+
+    pub fn put(&mut self, year: u16, day: usize, part: usize, input: u64, answer: i64, nanos: u128) {
+        self.entries.insert((year, day, part, input), (answer, nanos));
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut keys: Vec<_> = self.entries.keys().collect();
+        keys.sort();
+        let mut out = String::new();
+        for key in keys {
+            let (answer, nanos) = self.entries[key];
+            out.push_str(&format!("{:016x} {} {} {} {:016x} {} {}\n", self.build, key.0, key.1, key.2, key.3, answer, nanos));
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_reopen() {
+        let path = env::temp_dir().join(format!("aoc-cache-test-{}.txt", std::process::id()));
+        let mut cache = Cache::open(&path);
+        cache.put(2020, 1, 2, 0xabc, -42, 1000);
+        cache.save().unwrap();
+        // Answers of other builds are dropped
+        fs::write(&path, format!("{}0000000000000000 2020 1 1 abc 7 1\n", fs::read_to_string(&path).unwrap())).unwrap();
+        let cache = Cache::open(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(cache.get(2020, 1, 2, 0xabc), Some((-42, 1000)));
+        assert_eq!(cache.get(2020, 1, 2, 0xabd), None);
+        assert_eq!(cache.get(2020, 1, 1, 0xabc), None);
+    }
+}
@@ -0,0 +1,20 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub const RESET: &str = "\x1b[0m";
+pub const BOLD_YELLOW: &str = "\x1b[1;33m";
+pub const DIM: &str = "\x1b[2m";
+pub const RED: &str = "\x1b[31m";
+pub const GREEN: &str = "\x1b[32m";
+pub const YELLOW: &str = "\x1b[33m";
+pub const MAGENTA: &str = "\x1b[35m";
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed)
+}
+
+// The given ANSI colour code, or nothing if colours are disabled
+pub fn c(code: &'static str) -> &'static str {
+    if ENABLED.load(Ordering::Relaxed) { code } else { "" }
+}
@@ -0,0 +1,630 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use adventofcode2020::log;
+use crate::{spoiler, usage_error, vis};
+
+// The configuration file is looked up in the working directory by default
+pub const CONFIG_FILE: &str = "aoc.toml";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+// Key/value pairs of a config file, the keys of tables are prefixed with the table's name, like "bench.iterations"
+pub type Table = BTreeMap<String, Value>;
+
+// The characters of a line that are outside of strings, skipping the quotes and the escaped characters
+fn outside_strings(s: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let (mut in_string, mut escaped) = (false, false);
+    s.char_indices().filter(move |&(_, c)| {
+        let outside = !in_string && c != '"';
+        if escaped {
+            escaped = false
+        } else if in_string && c == '\\' {
+            escaped = true
+        } else if c == '"' {
+            in_string = !in_string
+        }
+        outside
+    })
+}
+
+// A string up to its closing quote and what follows it. \\ and \" are unescaped, other backslashes are kept
+// as they are (like in Windows paths)
+fn parse_string(s: &str) -> Option<(String, &str)> {
+    let mut res = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((res, &s[i + 1..])),
+            '\\' => match chars.next()? {
+                (_, c @ ('\\' | '"')) => res.push(c),
+                (_, c) => { res.push('\\'); res.push(c) }
+            },
+            c => res.push(c)
+        }
+    }
+    None
+}
+
+fn parse_value(s: &str) -> Result<Value, String> {
+    if let Some(s) = s.strip_prefix('"') {
+        match parse_string(s) {
+            Some((value, "")) => Ok(Value::Str(value)),
+            _ => Err(format!("bad string: \"{}", s))
+        }
+    } else if let Some(s) = s.strip_prefix('[') {
+        let s = s.strip_suffix(']').ok_or(format!("bad array: [{}", s))?.trim();
+        if s.is_empty() { return Ok(Value::Array(Vec::new())) }
+        let commas: Vec<usize> = outside_strings(s).filter(|&(_, c)| c == ',').map(|(i, _)| i).collect();
+        let starts = Some(0).into_iter().chain(commas.iter().map(|i| i + 1));
+        let ends = commas.iter().copied().chain(Some(s.len()));
+        starts.zip(ends).map(|(start, end)| parse_value(s[start..end].trim())).collect::<Result<Vec<Value>, String>>().map(Value::Array)
+    } else if s == "true" || s == "false" {
+        Ok(Value::Bool(s == "true"))
+    } else if let Ok(i) = s.replace('_', "").parse() {
+        Ok(Value::Int(i))
+    } else if let Ok(f) = s.parse() {
+        Ok(Value::Float(f))
+    } else {
+        Err(format!("bad value: {}", s))
+    }
+}
+
+// Parse the small subset of TOML we need: comments, [tables] and key = value pairs
+// with strings, integers, floats, booleans or flat arrays of these as values
+pub fn parse(s: &str) -> Result<Table, String> {
+    let mut table = Table::new();
+    let mut prefix = String::new();
+    for (n, line) in s.lines().enumerate() {
+        let err = |e: String| format!("line {}: {}", n + 1, e);
+
+        // Strip comments, unless the # is in a string
+        let end = outside_strings(line).find(|&(_, c)| c == '#').map(|(i, _)| i).unwrap_or(line.len());
+        let line = line[..end].trim();
+
+        if line.is_empty() { continue }
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name.strip_suffix(']').ok_or_else(|| err(format!("bad table header: {}", line)))?.trim();
+            prefix = format!("{}.", name);
+        } else {
+            let eq = line.find('=').ok_or_else(|| err(format!("expected key = value: {}", line)))?;
+            let key = format!("{}{}", prefix, line[..eq].trim());
+            let value = parse_value(line[eq + 1..].trim()).map_err(err)?;
+            if table.insert(key.clone(), value).is_some() {
+                return Err(err(format!("duplicate key: {}", key)))
+            }
+        }
+    }
+    Ok(table)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Switch {
+    Auto,
+    Always,
+    Never,
+}
+
+impl Switch {
+    fn parse(s: &str) -> Option<Switch> {
+        match s {
+            "auto" => Some(Switch::Auto),
+            "always" => Some(Switch::Always),
+            "never" => Some(Switch::Never),
+            _ => None
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Switch::Auto => "auto",
+            Switch::Always => "always",
+            Switch::Never => "never"
+        }
+    }
+
+    // Auto means on if the output is a terminal
+    pub fn resolve(&self, tty: bool) -> bool {
+        match self {
+            Switch::Auto => tty,
+            Switch::Always => true,
+            Switch::Never => false
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Options {
+    // Directory of the inputs and solutions, {year} is replaced with the year
+    pub input: String,
+    // Years to run, all of them if empty
+    pub years: Vec<u16>,
+    // Days to run, all of them if empty
+    pub days: Vec<usize>,
+    pub colour: Switch,
+    pub dashboard: Switch,
+    // How answers are shown, and the salt of their hashes in hash mode
+    pub spoilers: spoiler::Mode,
+    pub spoiler_salt: String,
+    // Number of runs when benchmarking a part
+    pub iterations: usize,
+    pub baseline: PathBuf,
+    // Slowdowns over this ratio compared to the baseline are regressions
+    pub regression_threshold: f64,
+    // External solver commands by year and day, year 0 applies to all years
+    pub external: BTreeMap<(u16, usize), String>,
+    pub external_timeout: Duration,
+    // Time budgets of verification: of all the days, of each day by default, and of single days by year and day
+    // (year 0 applies to all years)
+    pub budget: Duration,
+    pub day_budget: Option<Duration>,
+    pub day_budgets: BTreeMap<(u16, usize), Duration>,
+    // Reuse the answers of earlier runs of the same build on the same input
+    pub cache: bool,
+    pub cache_path: PathBuf,
+    // Where the key of the encrypted inputs is, unless it is in AOC_KEY
+    pub key_file: PathBuf,
+    // Where inputs are downloaded from, and the session token for it unless it is in AOC_SESSION
+    pub base_url: String,
+    pub session_file: PathBuf,
+    // Address of the solve service, how long it may spend on a request and how large inputs it accepts
+    pub listen: String,
+    pub serve_timeout: Duration,
+    pub max_input: usize,
+    pub explain: bool,
+    // Print the spans opened by the parts, and write them in the folded stack format to a file
+    pub trace: bool,
+    pub folded: Option<PathBuf>,
+    // Which messages the days log, and where to (stderr if not given)
+    pub log: Vec<log::Filter>,
+    pub log_file: Option<PathBuf>,
+    pub visualize: Option<vis::Target>,
+    pub watch: bool,
+    pub html: Option<PathBuf>,
+    pub save_baseline: bool,
+    // The alternate implementation of a part to shrink its input against
+    pub against: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            input: "input".to_string(),
+            years: Vec::new(),
+            days: Vec::new(),
+            colour: Switch::Auto,
+            dashboard: Switch::Auto,
+            spoilers: spoiler::Mode::Show,
+            spoiler_salt: "aoc".to_string(),
+            iterations: 10,
+            baseline: PathBuf::from(".aoc/baseline.txt"),
+            regression_threshold: 0.1,
+            external: BTreeMap::new(),
+            external_timeout: Duration::from_secs(10),
+            budget: Duration::from_secs(1),
+            day_budget: None,
+            day_budgets: BTreeMap::new(),
+            cache: false,
+            cache_path: PathBuf::from(".aoc/cache.txt"),
+            key_file: PathBuf::from(".aoc/key"),
+            base_url: "https://adventofcode.com".to_string(),
+            session_file: PathBuf::from(".aoc/session"),
+            listen: "127.0.0.1:2020".to_string(),
+            serve_timeout: Duration::from_secs(10),
+            max_input: 1 << 20,
+            explain: false,
+            trace: false,
+            folded: None,
+            log: Vec::new(),
+            log_file: None,
+            visualize: None,
+            watch: false,
+            html: None,
+            save_baseline: false,
+            against: None,
+        }
+    }
+}
+
+impl Options {
+    pub fn input_dir(&self, year: u16) -> PathBuf {
+        PathBuf::from(self.input.replace("{year}", &year.to_string()))
+    }
+
+    pub fn external(&self, year: u16, day: usize) -> Option<&String> {
+        self.external.get(&(year, day)).or_else(|| self.external.get(&(0, day)))
+    }
+
+    pub fn budget_of(&self, year: u16, day: usize) -> Option<Duration> {
+        self.day_budgets.get(&(year, day)).or_else(|| self.day_budgets.get(&(0, day))).copied().or(self.day_budget)
+    }
+
+    pub fn tracing(&self) -> bool {
+        self.trace || self.folded.is_some()
+    }
+
+    // Benchmarking (saving a baseline or writing a report) needs fresh timings, and explaining, tracing or
+    // visualizing a part needs the part itself, so these bypass the cache
+    pub fn use_cache(&self) -> bool {
+        self.cache && !self.save_baseline && self.html.is_none() && !self.explain && !self.tracing() && self.visualize.is_none()
+    }
+
+    // The days with an external solver are always run, so the solver is cross-checked against fresh answers
+    pub fn use_cache_for(&self, year: u16, day: usize) -> bool {
+        self.use_cache() && self.external(year, day).is_none()
+    }
+
+    fn apply(&mut self, table: &Table) -> Result<(), String> {
+        for (key, value) in table {
+            let bad = || format!("bad value for {}: {:?}", key, value);
+            if let Some(solver) = key.strip_prefix("external.").and_then(external_key) {
+                match value {
+                    Value::Str(cmd) => { self.external.insert(solver, cmd.clone()); },
+                    _ => return Err(bad())
+                }
+                continue
+            }
+            if let Some(day) = key.strip_prefix("verify.").and_then(external_key) {
+                self.day_budgets.insert(day, seconds(value).ok_or_else(bad)?);
+                continue
+            }
+            match (key.as_str(), value) {
+                ("input", Value::Str(s)) => self.input = s.clone(),
+                ("years", Value::Array(a)) => self.years = a.iter().map(|v| match v {
+                    Value::Int(y) => u16::try_from(*y).map_err(|_| bad()),
+                    _ => Err(bad())
+                }).collect::<Result<Vec<u16>, String>>()?,
+                ("colour", Value::Str(s)) => self.colour = Switch::parse(s).ok_or_else(bad)?,
+                ("dashboard", Value::Str(s)) => self.dashboard = Switch::parse(s).ok_or_else(bad)?,
+                ("spoilers.mode", Value::Str(s)) => self.spoilers = spoiler::Mode::parse(s).ok_or_else(bad)?,
+                ("spoilers.salt", Value::Str(s)) => self.spoiler_salt = s.clone(),
+                ("bench.iterations", Value::Int(n)) if *n > 0 => self.iterations = *n as usize,
+                ("baseline.path", Value::Str(s)) => self.baseline = PathBuf::from(s),
+                ("baseline.regression_threshold", Value::Float(f)) => self.regression_threshold = *f,
+                ("baseline.regression_threshold", Value::Int(i)) => self.regression_threshold = *i as f64,
+                ("external.timeout", Value::Int(i)) if *i > 0 => self.external_timeout = Duration::from_secs(*i as u64),
+                ("external.timeout", Value::Float(f)) if *f > 0.0 => self.external_timeout = Duration::from_secs_f64(*f),
+                ("cache.enabled", Value::Bool(b)) => self.cache = *b,
+                ("cache.path", Value::Str(s)) => self.cache_path = PathBuf::from(s),
+                ("crypto.key_file", Value::Str(s)) => self.key_file = PathBuf::from(s),
+                ("aoc.base_url", Value::Str(s)) => self.base_url = s.clone(),
+                ("aoc.session_file", Value::Str(s)) => self.session_file = PathBuf::from(s),
+                ("serve.listen", Value::Str(s)) => self.listen = s.clone(),
+                ("serve.timeout", Value::Int(i)) if *i > 0 => self.serve_timeout = Duration::from_secs(*i as u64),
+                ("serve.timeout", Value::Float(f)) if *f > 0.0 => self.serve_timeout = Duration::from_secs_f64(*f),
+                ("serve.max_input", Value::Int(i)) if *i > 0 => self.max_input = *i as usize,
+                ("log.filters", Value::Array(a)) => self.log = a.iter().map(|v| match v {
+                    Value::Str(s) => log::Filter::parse(s).ok_or_else(bad),
+                    _ => Err(bad())
+                }).collect::<Result<Vec<log::Filter>, String>>()?,
+                ("log.file", Value::Str(s)) => self.log_file = Some(PathBuf::from(s)),
+                ("log.filters", _) | ("log.file", _) => return Err(bad()),
+                ("verify.budget", _) => self.budget = seconds(value).ok_or_else(bad)?,
+                ("verify.day_budget", _) => self.day_budget = Some(seconds(value).ok_or_else(bad)?),
+                ("input", _) | ("years", _) | ("colour", _) | ("dashboard", _) | ("bench.iterations", _) |
+                ("baseline.path", _) | ("baseline.regression_threshold", _) | ("external.timeout", _) |
+                ("cache.enabled", _) | ("cache.path", _) | ("spoilers.mode", _) | ("spoilers.salt", _) |
+                ("crypto.key_file", _) | ("aoc.base_url", _) | ("aoc.session_file", _) |
+                ("serve.listen", _) | ("serve.timeout", _) | ("serve.max_input", _) => return Err(bad()),
+                _ => return Err(format!("unknown key: {}", key))
+            }
+        }
+        Ok(())
+    }
+
+    // The effective configuration in the config file's format
+    pub fn show(&self) -> String {
+        let years: Vec<String> = self.years.iter().map(|y| y.to_string()).collect();
+        let mut out = format!("input = {}\n\
+                               years = [{}]\n\
+                               colour = {}\n\
+                               dashboard = {}\n\
+                               \n\
+                               [spoilers]\n\
+                               mode = {}\n\
+                               salt = {}\n\
+                               \n\
+                               [bench]\n\
+                               iterations = {}\n\
+                               \n\
+                               [baseline]\n\
+                               path = {}\n\
+                               regression_threshold = {:?}\n\
+                               \n\
+                               [cache]\n\
+                               enabled = {}\n\
+                               path = {}\n\
+                               \n\
+                               [crypto]\n\
+                               key_file = {}\n\
+                               \n\
+                               [aoc]\n\
+                               base_url = {}\n\
+                               session_file = {}\n\
+                               \n\
+                               [serve]\n\
+                               listen = {}\n\
+                               timeout = {:?}\n\
+                               max_input = {}\n\
+                               \n\
+                               [verify]\n\
+                               budget = {:?}\n",
+                              string(&self.input), years.join(", "), string(self.colour.name()), string(self.dashboard.name()),
+                              string(self.spoilers.name()), string(&self.spoiler_salt),
+                              self.iterations, string(self.baseline.display()), self.regression_threshold,
+                              self.cache, string(self.cache_path.display()), string(self.key_file.display()),
+                              string(&self.base_url), string(self.session_file.display()),
+                              string(&self.listen), self.serve_timeout.as_secs_f64(), self.max_input,
+                              self.budget.as_secs_f64());
+        if let Some(budget) = self.day_budget {
+            out.push_str(&format!("day_budget = {:?}\n", budget.as_secs_f64()));
+        }
+        for ((year, day), budget) in &self.day_budgets {
+            let year = if *year == 0 { String::new() } else { format!("{}.", year) };
+            out.push_str(&format!("{}d{:02} = {:?}\n", year, day, budget.as_secs_f64()));
+        }
+        let filters: Vec<String> = self.log.iter().map(string).collect();
+        out.push_str(&format!("\n[log]\nfilters = [{}]\n", filters.join(", ")));
+        if let Some(file) = &self.log_file {
+            out.push_str(&format!("file = {}\n", string(file.display())));
+        }
+        out.push_str(&format!("\n[external]\ntimeout = {:?}\n", self.external_timeout.as_secs_f64()));
+        for ((year, day), cmd) in &self.external {
+            let year = if *year == 0 { String::new() } else { format!("{}.", year) };
+            out.push_str(&format!("{}d{:02} = {}\n", year, day, string(cmd)));
+        }
+        out
+    }
+}
+
+// A string value in the config file's format, with its backslashes and quotes escaped for parse_value
+fn string(s: impl fmt::Display) -> String {
+    format!("\"{}\"", s.to_string().replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// A positive number of seconds
+fn seconds(value: &Value) -> Option<Duration> {
+    match value {
+        Value::Int(i) if *i > 0 => Some(Duration::from_secs(*i as u64)),
+        Value::Float(f) if *f > 0.0 => Some(Duration::from_secs_f64(*f)),
+        _ => None
+    }
+}
+
+// The [YEAR/]DAY=VALUE arguments of flags, like --external 2020/11=./d11
+fn day_argument(v: &str) -> Option<((u16, usize), &str)> {
+    let (day, value) = v.split_once('=')?;
+    let key = match day.split_once('/') {
+        Some((year, day)) => external_key(&format!("{}.d{}", year, day)),
+        None => external_key(&format!("d{}", day))
+    };
+    Some((key?, value))
+}
+
+// External solver (and day budget) keys are either "dNN" for all years or "YYYY.dNN" for a single year
+fn external_key(key: &str) -> Option<(u16, usize)> {
+    let (year, day) = match key.find('.') {
+        Some(i) => (key[..i].parse().ok()?, &key[i + 1..]),
+        None => (0, key)
+    };
+    Some((year, day.strip_prefix('d')?.parse().ok()?))
+}
+
+fn load(path: &Path, required: bool) -> Table {
+    if !path.is_file() {
+        if required { usage_error(&format!("{} not found", path.display())) }
+        return Table::new()
+    }
+    let content = fs::read_to_string(path).unwrap_or_else(|e| usage_error(&format!("{}: {}", path.display(), e)));
+    parse(&content).unwrap_or_else(|e| usage_error(&format!("{}: {}", path.display(), e)))
+}
+
+// Options from the config file, overridden by the command line flags, and the positional arguments
+pub fn options(args: &[String]) -> (Options, Vec<String>) {
+    let mut opts = Options::default();
+    let mut positional = Vec::new();
+
+    // The config file has to be loaded before the other flags are applied
+    let (path, required) = match args.iter().position(|a| a == "--config") {
+        Some(i) => match args.get(i + 1) {
+            Some(path) => (Path::new(path), true),
+            None => usage_error("--config expects a file name")
+        },
+        None => (Path::new(CONFIG_FILE), false)
+    };
+    opts.apply(&load(path, required)).unwrap_or_else(|e| usage_error(&format!("{}: {}", path.display(), e)));
+
+    // The years on the command line replace the ones in the config file
+    let mut years_given = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |what: &str| match args.next() {
+            Some(v) => v.clone(),
+            None => usage_error(&format!("{} expects {}", arg, what))
+        };
+        match arg.as_str() {
+            "--config" => { value("a file name"); },
+            "--input" => opts.input = value("a directory"),
+            "--year" => match value("a year").parse() {
+                Ok(y) => {
+                    if !years_given { opts.years.clear() }
+                    years_given = true;
+                    opts.years.push(y)
+                },
+                Err(_) => usage_error("--year expects a year")
+            },
+            "--day" => match value("a day number").parse() {
+                Ok(d) => opts.days.push(d),
+                Err(_) => usage_error("--day expects a day number")
+            },
+            "--colour" => match Switch::parse(&value("auto, always or never")) {
+                Some(s) => opts.colour = s,
+                None => usage_error("--colour expects auto, always or never")
+            },
+            "--spoilers" => match spoiler::Mode::parse(&value("show, mask or hash")) {
+                Some(mode) => opts.spoilers = mode,
+                None => usage_error("--spoilers expects show, mask or hash")
+            },
+            "--spoiler-free" => opts.spoilers = spoiler::Mode::Hash,
+            "--dashboard" => opts.dashboard = Switch::Always,
+            "--no-dashboard" => opts.dashboard = Switch::Never,
+            "--iterations" => match value("a number").parse() {
+                Ok(n) if n > 0 => opts.iterations = n,
+                _ => usage_error("--iterations expects a positive number")
+            },
+            "--baseline" => opts.baseline = PathBuf::from(value("a file name")),
+            "--threshold" => match value("a ratio").parse() {
+                Ok(t) => opts.regression_threshold = t,
+                Err(_) => usage_error("--threshold expects a ratio, like 0.1")
+            },
+            "--external" => match day_argument(&value("[YEAR/]DAY=CMD")) {
+                Some((solver, cmd)) => { opts.external.insert(solver, cmd.to_string()); },
+                None => usage_error("--external expects [YEAR/]DAY=CMD")
+            },
+            "--budget" => match value("seconds").parse() {
+                Ok(t) if t > 0.0 => opts.budget = Duration::from_secs_f64(t),
+                _ => usage_error("--budget expects a positive number of seconds")
+            },
+            "--day-budget" => {
+                // SECONDS for every day, or [YEAR/]DAY=SECONDS for a single one
+                let v = value("[[YEAR/]DAY=]SECONDS");
+                let (day, t) = day_argument(&v).map_or((None, v.as_str()), |(day, t)| (Some(day), t));
+                match (day, t.parse()) {
+                    (Some(day), Ok(t)) if t > 0.0 => { opts.day_budgets.insert(day, Duration::from_secs_f64(t)); },
+                    (None, Ok(t)) if t > 0.0 => opts.day_budget = Some(Duration::from_secs_f64(t)),
+                    _ => usage_error("--day-budget expects [[YEAR/]DAY=]SECONDS")
+                }
+            },
+            "--external-timeout" => match value("seconds").parse() {
+                Ok(t) if t > 0.0 => opts.external_timeout = Duration::from_secs_f64(t),
+                _ => usage_error("--external-timeout expects a positive number of seconds")
+            },
+            "--listen" => opts.listen = value("an address"),
+            "--serve-timeout" => match value("seconds").parse() {
+                Ok(t) if t > 0.0 => opts.serve_timeout = Duration::from_secs_f64(t),
+                _ => usage_error("--serve-timeout expects a positive number of seconds")
+            },
+            "--max-input" => match value("a number of bytes").parse() {
+                Ok(n) if n > 0 => opts.max_input = n,
+                _ => usage_error("--max-input expects a positive number of bytes")
+            },
+            "--base-url" => opts.base_url = value("a URL"),
+            "--session-file" => opts.session_file = PathBuf::from(value("a file name")),
+            "--key-file" => opts.key_file = PathBuf::from(value("a file name")),
+            "--cache" => opts.cache = true,
+            "--no-cache" => opts.cache = false,
+            "--explain" => opts.explain = true,
+            "--trace" => opts.trace = true,
+            "--log" => match log::Filter::parse(&value("[[YEAR/][dDAY][/pPART]=]LEVEL")) {
+                Some(filter) => opts.log.push(filter),
+                None => usage_error("--log expects [[YEAR/][dDAY][/pPART]=]LEVEL, the level is off, error, warn, info, debug or trace")
+            },
+            "--log-file" => opts.log_file = Some(PathBuf::from(value("a file name"))),
+            "--folded" => opts.folded = Some(PathBuf::from(value("a file name"))),
+            "--html" => opts.html = Some(PathBuf::from(value("a file name"))),
+            "--save-baseline" => opts.save_baseline = true,
+            "--against" => opts.against = Some(value("the name of an alternate implementation")),
+            "--visualize" => opts.visualize = Some(vis::Target::Terminal),
+            "--watch" => opts.watch = true,
+            _ if arg.starts_with("--visualize=") => {
+                opts.visualize = Some(vis::Target::Ppm(PathBuf::from(&arg["--visualize=".len()..])))
+            },
+            _ if !arg.starts_with("--") => positional.push(arg.clone()),
+            _ => usage_error(&format!("unknown argument: {}", arg))
+        }
+    }
+    (opts, positional)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let table = parse("# comment\n\
+                           input = \"in/{year}\" # trailing comment\n\
+                           years = [2020, 2021]\n\
+                           \n\
+                           [baseline]\n\
+                           regression_threshold = 0.25\n\
+                           path = \"a#b\"\n").unwrap();
+        assert_eq!(table.get("input"), Some(&Value::Str("in/{year}".to_string())));
+        assert_eq!(table.get("years"), Some(&Value::Array(vec![Value::Int(2020), Value::Int(2021)])));
+        assert_eq!(table.get("baseline.regression_threshold"), Some(&Value::Float(0.25)));
+        assert_eq!(table.get("baseline.path"), Some(&Value::Str("a#b".to_string())));
+        assert!(parse("years = [2020").is_err());
+        let table = parse("cmd = \"echo \\\"a # b\\\" C:\\d\\\\\" # comment\n\
+                           filters = [\"a,b\", \"\\\"\"]").unwrap();
+        assert_eq!(table.get("cmd"), Some(&Value::Str("echo \"a # b\" C:\\d\\".to_string())));
+        assert_eq!(table.get("filters"), Some(&Value::Array(vec![Value::Str("a,b".to_string()), Value::Str("\"".to_string())])));
+        assert!(parse("a = \"b\\\"").is_err());
+        assert!(Options::default().apply(&parse("years = [67556]").unwrap()).is_err());
+        assert!(parse("a = 1\na = 2").is_err());
+    }
+
+    #[test]
+    fn show_round_trips() {
+        let mut opts = Options { years: vec![2020], iterations: 3, cache: true, spoilers: spoiler::Mode::Mask, ..Default::default() };
+        opts.input = "C:\\aoc\\{year}".to_string();
+        opts.external.insert((0, 1), "python3 d01.py".to_string());
+        opts.external.insert((0, 2), "sed 's/\\\\n/ /'".to_string());
+        opts.external.insert((2020, 11), "./d11 \"a # b\" \\".to_string());
+        opts.day_budget = Some(Duration::from_millis(200));
+        opts.log = vec![log::Filter::parse("info").unwrap(), log::Filter::parse("2020/d14/p2=trace").unwrap()];
+        opts.log_file = Some(PathBuf::from(".aoc/log.txt"));
+        opts.day_budgets.insert((0, 15), Duration::from_millis(1500));
+        opts.day_budgets.insert((2020, 11), Duration::from_millis(500));
+        let mut parsed = Options::default();
+        parsed.apply(&parse(&opts.show()).unwrap()).unwrap();
+        assert_eq!(parsed.show(), opts.show());
+        assert_eq!(parsed.budget_of(2020, 11), Some(Duration::from_millis(500)));
+        assert_eq!(parsed.budget_of(2021, 11), Some(Duration::from_millis(200)));
+        assert_eq!(parsed.budget_of(2021, 15), Some(Duration::from_millis(1500)));
+        assert!(parsed.apply(&parse("colour = \"sometimes\"").unwrap()).is_err());
+        assert!(parsed.apply(&parse("colours = \"never\"").unwrap()).is_err());
+        assert_eq!(parsed.input, "C:\\aoc\\{year}");
+        assert_eq!(parsed.external[&(0, 2)], "sed 's/\\\\n/ /'");
+        assert_eq!(parsed.external[&(2020, 11)], "./d11 \"a # b\" \\");
+    }
+
+    #[test]
+    fn cache_bypass() {
+        let mut opts = Options { cache: true, ..Default::default() };
+        opts.external.insert((0, 1), "python3 d01.py".to_string());
+        opts.external.insert((2020, 11), "./d11".to_string());
+        assert!(opts.use_cache_for(2020, 2));
+        assert!(!opts.use_cache_for(2020, 1));
+        assert!(!opts.use_cache_for(2021, 1));
+        assert!(!opts.use_cache_for(2020, 11));
+        assert!(opts.use_cache_for(2021, 11));
+        opts.explain = true;
+        assert!(!opts.use_cache_for(2020, 2));
+    }
+
+    #[test]
+    fn flags_override_the_config_file() {
+        let path = std::env::temp_dir().join(format!("aoc-config-test-{}.toml", std::process::id()));
+        fs::write(&path, "years = [2019, 2020]\n\n[bench]\niterations = 5\n").unwrap();
+        let args = |flags: &[&str]| {
+            let mut args = vec!["--config".to_string(), path.display().to_string()];
+            args.extend(flags.iter().map(|f| f.to_string()));
+            options(&args).0
+        };
+        assert_eq!(args(&[]).years, [2019, 2020]);
+        assert_eq!(args(&["--year", "2021"]).years, [2021]);
+        assert_eq!(args(&["--year", "2021", "--iterations", "2", "--year", "2020"]).years, [2021, 2020]);
+        assert_eq!(args(&["--iterations", "2"]).iterations, 2);
+        fs::remove_file(&path).unwrap();
+    }
+}
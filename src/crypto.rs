@@ -0,0 +1,261 @@
+use std::convert::TryInto;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+// Inputs are encrypted with ChaCha20-Poly1305 (RFC 8439), implemented here to keep the project free of dependencies
+
+pub type Key = [u8; 32];
+
+// The key is looked up in this environment variable first, then in the key file, as 64 hex digits
+pub const KEY_VAR: &str = "AOC_KEY";
+
+// Encrypted files are the magic, a random nonce, the ciphertext and the tag
+const MAGIC: &[u8] = b"aocenc1\n";
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+fn le32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]); s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]); s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]); s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]); s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+fn chacha20_block(key: &Key, counter: u32, nonce: &[u8]) -> [u8; 64] {
+    let mut init = [0u32; 16];
+    init[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for i in 0..8 { init[4 + i] = le32(&key[4 * i..]) }
+    init[12] = counter;
+    for i in 0..3 { init[13 + i] = le32(&nonce[4 * i..]) }
+
+    let mut s = init;
+    for _ in 0..10 {
+        quarter_round(&mut s, 0, 4, 8, 12);
+        quarter_round(&mut s, 1, 5, 9, 13);
+        quarter_round(&mut s, 2, 6, 10, 14);
+        quarter_round(&mut s, 3, 7, 11, 15);
+        quarter_round(&mut s, 0, 5, 10, 15);
+        quarter_round(&mut s, 1, 6, 11, 12);
+        quarter_round(&mut s, 2, 7, 8, 13);
+        quarter_round(&mut s, 3, 4, 9, 14);
+    }
+    let mut out = [0u8; 64];
+    for i in 0..16 {
+        out[4 * i..4 * i + 4].copy_from_slice(&s[i].wrapping_add(init[i]).to_le_bytes());
+    }
+    out
+}
+
+// Encrypts and decrypts in place
+fn chacha20(key: &Key, counter: u32, nonce: &[u8], data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let stream = chacha20_block(key, counter.wrapping_add(i as u32), nonce);
+        for (b, s) in chunk.iter_mut().zip(stream.iter()) { *b ^= s }
+    }
+}
+
+// Poly1305 with the accumulator and r in 26 bit limbs
+fn poly1305(key: &[u8], msg: &[u8]) -> [u8; 16] {
+    const M: u64 = 0x3ffffff;
+    let r = [(le32(&key[0..]) & 0x3ffffff) as u64,
+             ((le32(&key[3..]) >> 2) & 0x3ffff03) as u64,
+             ((le32(&key[6..]) >> 4) & 0x3ffc0ff) as u64,
+             ((le32(&key[9..]) >> 6) & 0x3f03fff) as u64,
+             ((le32(&key[12..]) >> 8) & 0x00fffff) as u64];
+    let s = [0, r[1] * 5, r[2] * 5, r[3] * 5, r[4] * 5];
+    let mut h = [0u64; 5];
+
+    for chunk in msg.chunks(16) {
+        let mut block = [0u8; 17];
+        block[..chunk.len()].copy_from_slice(chunk);
+        block[chunk.len()] = 1;
+        h[0] += (le32(&block[0..]) & 0x3ffffff) as u64;
+        h[1] += ((le32(&block[3..]) >> 2) & 0x3ffffff) as u64;
+        h[2] += ((le32(&block[6..]) >> 4) & 0x3ffffff) as u64;
+        h[3] += ((le32(&block[9..]) >> 6) & 0x3ffffff) as u64;
+        h[4] += ((le32(&block[12..]) >> 8) | ((block[16] as u32) << 24)) as u64;
+
+        let d = [h[0] * r[0] + h[1] * s[4] + h[2] * s[3] + h[3] * s[2] + h[4] * s[1],
+                 h[0] * r[1] + h[1] * r[0] + h[2] * s[4] + h[3] * s[3] + h[4] * s[2],
+                 h[0] * r[2] + h[1] * r[1] + h[2] * r[0] + h[3] * s[4] + h[4] * s[3],
+                 h[0] * r[3] + h[1] * r[2] + h[2] * r[1] + h[3] * r[0] + h[4] * s[4],
+                 h[0] * r[4] + h[1] * r[3] + h[2] * r[2] + h[3] * r[1] + h[4] * r[0]];
+        let mut c = 0;
+        for i in 0..5 {
+            let di = d[i] + c;
+            h[i] = di & M;
+            c = di >> 26;
+        }
+        h[0] += c * 5;
+        h[1] += h[0] >> 26;
+        h[0] &= M;
+    }
+
+    // Fully carry h, then reduce it modulo 2^130 - 5
+    let mut c = 0;
+    for limb in h.iter_mut().skip(1) {
+        *limb += c;
+        c = *limb >> 26;
+        *limb &= M;
+    }
+    h[0] += c * 5;
+    h[1] += h[0] >> 26;
+    h[0] &= M;
+    let mut g = [0u64; 5];
+    let mut c = 5;
+    for i in 0..5 {
+        g[i] = h[i] + c;
+        c = g[i] >> 26;
+        g[i] &= M;
+    }
+    // If h + 5 overflows 2^130, h >= p and h - p = g
+    if c != 0 { h = g }
+
+    let acc = (h[0] | h[1] << 26 | h[2] << 52) as u128 | ((h[2] >> 12 | h[3] << 14 | h[4] << 40) as u128) << 64;
+    let pad = u128::from_le_bytes(key[16..32].try_into().unwrap());
+    acc.wrapping_add(pad).to_le_bytes()
+}
+
+fn pad16(data: &mut Vec<u8>) {
+    while !data.len().is_multiple_of(16) { data.push(0) }
+}
+
+fn tag(key: &Key, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+    let otk = chacha20_block(key, 0, nonce);
+    let mut mac_data = aad.to_vec();
+    pad16(&mut mac_data);
+    mac_data.extend_from_slice(ciphertext);
+    pad16(&mut mac_data);
+    mac_data.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+    mac_data.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+    poly1305(&otk[..32], &mac_data)
+}
+
+// The ciphertext followed by the tag
+fn seal(key: &Key, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut out = plaintext.to_vec();
+    chacha20(key, 1, nonce, &mut out);
+    let tag = tag(key, nonce, aad, &out);
+    out.extend_from_slice(&tag);
+    out
+}
+
+fn open(key: &Key, nonce: &[u8], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < TAG_LEN { return None }
+    let (ciphertext, expected) = sealed.split_at(sealed.len() - TAG_LEN);
+    // Compare without stopping at the first difference
+    let diff = tag(key, nonce, aad, ciphertext).iter().zip(expected).fold(0, |acc, (a, b)| acc | (a ^ b));
+    if diff != 0 { return None }
+    let mut out = ciphertext.to_vec();
+    chacha20(key, 1, nonce, &mut out);
+    Some(out)
+}
+
+pub fn random_bytes(buf: &mut [u8]) -> io::Result<()> {
+    File::open("/dev/urandom")?.read_exact(buf)
+}
+
+pub fn encrypt(key: &Key, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    random_bytes(&mut nonce)?;
+    let mut out = MAGIC.to_vec();
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&seal(key, &nonce, MAGIC, plaintext));
+    Ok(out)
+}
+
+pub fn decrypt(key: &Key, data: &[u8]) -> Result<Vec<u8>, String> {
+    let data = data.strip_prefix(MAGIC).ok_or("not an encrypted file")?;
+    if data.len() < NONCE_LEN { return Err("truncated file".to_string()) }
+    let (nonce, sealed) = data.split_at(NONCE_LEN);
+    open(key, nonce, MAGIC, sealed).ok_or_else(|| "wrong key or corrupted file".to_string())
+}
+
+fn parse_key(s: &str) -> Option<Key> {
+    let s = s.trim();
+    if s.len() != 64 || !s.is_ascii() { return None }
+    let mut key = [0u8; 32];
+    for (i, b) in key.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(key)
+}
+
+// The key from the environment or the key file
+pub fn load_key(key_file: &Path) -> Result<Key, String> {
+    if let Ok(s) = env::var(KEY_VAR) {
+        return parse_key(&s).ok_or_else(|| format!("{} is not 64 hex digits", KEY_VAR))
+    }
+    let s = fs::read_to_string(key_file)
+        .map_err(|e| format!("no key in {} and cannot read {}: {}", KEY_VAR, key_file.display(), e))?;
+    parse_key(&s).ok_or_else(|| format!("{} is not 64 hex digits", key_file.display()))
+}
+
+// A new random key in the key file's format
+pub fn generate_key() -> io::Result<String> {
+    let mut key = [0u8; 32];
+    random_bytes(&mut key)?;
+    Ok(key.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        let s: String = s.split_whitespace().collect();
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    const SUNSCREEN: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+    // The test vectors of RFC 8439
+    #[test]
+    fn rfc8439_chacha20() {
+        let key: Key = (0..32).collect::<Vec<u8>>().try_into().unwrap();
+        let mut data = SUNSCREEN.to_vec();
+        chacha20(&key, 1, &hex("000000000000004a00000000"), &mut data);
+        assert_eq!(data, hex("6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b
+                              f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8
+                              07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736
+                              5af90bbf74a35be6b40b8eedf2785e42874d"));
+    }
+
+    #[test]
+    fn rfc8439_poly1305() {
+        let key = hex("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b");
+        assert_eq!(poly1305(&key, b"Cryptographic Forum Research Group").to_vec(), hex("a8061dc1305136c6c22b8baf0c0127a9"));
+    }
+
+    #[test]
+    fn rfc8439_aead() {
+        let key: Key = (0x80..0xa0).collect::<Vec<u8>>().try_into().unwrap();
+        let nonce = hex("070000004041424344454647");
+        let aad = hex("50515253c0c1c2c3c4c5c6c7");
+        let sealed = seal(&key, &nonce, &aad, SUNSCREEN);
+        assert_eq!(sealed, hex("d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6
+                                3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36
+                                92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc
+                                3ff4def08e4b7a9de576d26586cec64b6116
+                                1ae10b594f09e26a7e902ecbd0600691"));
+        assert_eq!(open(&key, &nonce, &aad, &sealed).as_deref(), Some(SUNSCREEN));
+        let mut tampered = sealed;
+        tampered[0] ^= 1;
+        assert_eq!(open(&key, &nonce, &aad, &tampered), None);
+    }
+
+    #[test]
+    fn encrypt_round_trip() {
+        let key = parse_key(&generate_key().unwrap()).unwrap();
+        let encrypted = encrypt(&key, b"1721\n979\n").unwrap();
+        assert_eq!(decrypt(&key, &encrypted), Ok(b"1721\n979\n".to_vec()));
+        assert!(decrypt(&parse_key(&"0".repeat(64)).unwrap(), &encrypted).is_err());
+        assert!(decrypt(&key, b"1721\n979\n").is_err());
+    }
+}
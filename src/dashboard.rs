@@ -0,0 +1,133 @@
+use crate::colour::{self, c};
+use crate::{DayResult, Year};
+
+const CELL_W: usize = 11;
+
+#[derive(Debug, PartialEq)]
+enum Stars {
+    Both,
+    One,
+    Wrong,
+    NotImplemented,
+    NoInput,
+    Skipped,
+}
+
+// The stars of a day and its time, if it was run, marking the times of cached results
+fn stars(year: &Year, day: usize, results: &[DayResult]) -> (Stars, Option<(u128, bool)>) {
+    if day > year.day_parsers.len() {
+        return (Stars::NotImplemented, None)
+    }
+    match results.iter().find(|r| r.day == day) {
+        None => (Stars::Skipped, None),
+        Some(r) => match r.parse_nanos {
+            None => (Stars::NoInput, None),
+            Some(_) => {
+                let ok = r.parts.iter().filter(|p| p.ok()).count();
+                let stars = if ok == 0 {
+                    Stars::Wrong
+                } else if ok == r.parts.len() {
+                    Stars::Both
+                } else {
+                    Stars::One
+                };
+                (stars, Some((r.nanos(), r.cached)))
+            }
+        }
+    }
+}
+
+fn format_time(nanos: u128) -> String {
+    if nanos < 1_000_000 {
+        format!("{:.1} μs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.1} ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2} s", nanos as f64 / 1e9)
+    }
+}
+
+// Green below a millisecond, yellow below 100 milliseconds, red above
+fn speed_colour(nanos: u128) -> &'static str {
+    if nanos < 1_000_000 {
+        c(colour::GREEN)
+    } else if nanos < 100_000_000 {
+        c(colour::YELLOW)
+    } else {
+        c(colour::RED)
+    }
+}
+
+// Show the results of a year as an advent calendar: a 5x5 grid of days
+pub fn print(year: &Year, results: &[DayResult]) {
+    let border = format!("+{}", format!("{}+", "-".repeat(CELL_W)).repeat(5));
+    println!();
+    println!("y{:04}", year.year);
+    println!("{}", border);
+    for row in 0..5 {
+        let mut top = String::from("|");
+        let mut bottom = String::from("|");
+        for col in 0..5 {
+            let day = row * 5 + col + 1;
+            let (stars, nanos) = stars(year, day, results);
+            let (label, paint) = match stars {
+                Stars::Both => ("**", c(colour::BOLD_YELLOW)),
+                Stars::One => ("* ", c(colour::YELLOW)),
+                Stars::Wrong => ("xx", c(colour::RED)),
+                Stars::NotImplemented => ("--", c(colour::DIM)),
+                Stars::NoInput => ("??", c(colour::MAGENTA)),
+                Stars::Skipped => ("  ", "")
+            };
+            top.push_str(&format!(" {:02} {}{:>w$}{} |", day, paint, label, c(colour::RESET), w = CELL_W - 5));
+            match nanos {
+                Some((nanos, cached)) => {
+                    let time = format!("{}{}", if cached { "~" } else { "" }, format_time(nanos));
+                    bottom.push_str(&format!(" {}{:>w$}{} |", speed_colour(nanos), time, c(colour::RESET), w = CELL_W - 2))
+                },
+                None => bottom.push_str(&format!("{:w$}|", "", w = CELL_W))
+            }
+        }
+        println!("{}", top);
+        println!("{}", bottom);
+        println!("{}", border);
+    }
+
+    let total: u128 = results.iter().map(|r| r.nanos()).sum();
+    let stars: usize = results.iter().map(|r| r.parts.iter().filter(|p| p.ok()).count()).sum();
+    let cached = results.iter().filter(|r| r.cached).count();
+    if cached > 0 {
+        println!("{} stars in {} ({} days from the cache, marked with ~)", stars, format_time(total), cached);
+    } else {
+        println!("{} stars in {}", stars, format_time(total));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adventofcode2020::y2020;
+    use crate::baseline::tests::result;
+    use crate::spoiler::Solution;
+
+    #[test]
+    fn stars_of_days() {
+        let year = y2020::year();
+        let mut one = result(2, 10, &[20, 30]);
+        one.parts[1].expected = Solution::parse("43").unwrap();
+        let mut wrong = result(3, 10, &[20]);
+        wrong.parts[0].expected = Solution::parse("43").unwrap();
+        let mut cached = result(4, 10, &[20]);
+        cached.cached = true;
+        let missing = DayResult { day: 5, parse_nanos: None, parse_spans: Vec::new(), parts: Vec::new(), cached: false };
+        let results = [result(1, 10, &[20, 30]), one, wrong, cached, missing];
+
+        assert_eq!(stars(&year, 1, &results), (Stars::Both, Some((60, false))));
+        assert_eq!(stars(&year, 2, &results), (Stars::One, Some((60, false))));
+        assert_eq!(stars(&year, 3, &results), (Stars::Wrong, Some((30, false))));
+        assert_eq!(stars(&year, 4, &results), (Stars::Both, Some((30, true))));
+        assert_eq!(stars(&year, 5, &results), (Stars::NoInput, None));
+        assert_eq!(stars(&year, 6, &results), (Stars::Skipped, None));
+        assert_eq!(stars(&year, 25, &results), (Stars::NotImplemented, None));
+        assert_eq!((format_time(999_949), format_time(1_500_000), format_time(2_345_000_000)), ("999.9 μs".to_string(), "1.5 ms".to_string(), "2.35 s".to_string()));
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+use std::io::{Read, Write};
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// The answers printed by an external solver and the time it took to run it
+pub struct Outcome {
+    pub answers: Result<Vec<i64>, String>,
+    pub nanos: u128,
+}
+
+// Run an external solver with the input on its stdin and parse its stdout as whitespace separated answers
+pub fn run(cmd: &str, input: &str, timeout: Duration) -> Outcome {
+    let t = Instant::now();
+    let answers = run_cmd(cmd, input, timeout);
+    Outcome { answers, nanos: t.elapsed().as_nanos() }
+}
+
+fn run_cmd(cmd: &str, input: &str, timeout: Duration) -> Result<Vec<i64>, String> {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(cmd);
+    answers(&output(command, input, timeout).map_err(|e| e.to_string())?)
+}
+
+// The answers of the parts in the output of an external solver, separated by whitespace
+pub fn answers(out: &str) -> Result<Vec<i64>, String> {
+    out.split_whitespace()
+        .map(|a| a.parse().map_err(|_| format!("not an answer: {}", a)))
+        .collect()
+}
+
+// Why a command gave no output
+#[derive(Debug)]
+pub enum Error {
+    Start(String),
+    TimedOut(Duration),
+    Failed(ExitStatus),
+    Output(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Start(e) => write!(f, "cannot start: {}", e),
+            Error::TimedOut(timeout) => write!(f, "timed out after {:?}", timeout),
+            Error::Failed(status) => write!(f, "failed with {}", status),
+            Error::Output(e) => write!(f, "{}", e)
+        }
+    }
+}
+
+// Run a command with the input on its stdin and return its stdout, unless it fails or times out.
+// Its stderr is inherited, unless the command redirects it.
+pub fn output(mut command: Command, input: &str, timeout: Duration) -> Result<String, Error> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::Start(e.to_string()))?;
+
+    // Feed stdin and drain stdout on separate threads, so a solver can't deadlock us on a full pipe
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_string();
+    let writer = thread::spawn(move || { let _ = stdin.write_all(input.as_bytes()); });
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut out = String::new();
+        stdout.read_to_string(&mut out).map(|_| out)
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait().map_err(|e| Error::Output(e.to_string()))? {
+            Some(status) => break status,
+            None if start.elapsed() > timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::TimedOut(timeout))
+            },
+            None => thread::sleep(Duration::from_millis(1))
+        }
+    };
+    let _ = writer.join();
+    let out = reader.join().unwrap().map_err(|e| Error::Output(e.to_string()))?;
+    if !status.success() {
+        return Err(Error::Failed(status))
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_from_stdout() {
+        let timeout = Duration::from_secs(5);
+        assert_eq!(run("wc -l; echo 42", "a\nb\nc\n", timeout).answers, Ok(vec![3, 42]));
+        assert!(run("echo nope", "", timeout).answers.is_err());
+        assert!(run("exit 3", "", timeout).answers.is_err());
+        assert!(run("sleep 5", "", Duration::from_millis(50)).answers.unwrap_err().starts_with("timed out"));
+        let sleep = || { let mut command = Command::new("sleep"); command.arg("5"); command };
+        assert!(matches!(output(sleep(), "", Duration::from_millis(50)), Err(Error::TimedOut(_))));
+        assert!(matches!(output(Command::new("false"), "", timeout), Err(Error::Failed(_))));
+    }
+}
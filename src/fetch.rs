@@ -0,0 +1,145 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::colour::{self, c};
+use crate::crypto::{self, Key};
+use crate::leaderboard::release;
+use crate::{http, input, selected_years, usage_error, Options};
+
+// AoC asks tools not to hammer the servers, so downloads are at least this far apart
+const THROTTLE: Duration = Duration::from_secs(3);
+
+// The session token is looked up in this environment variable first, then in the session file
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+pub fn session(session_file: &Path) -> Result<String, String> {
+    let token = match env::var(SESSION_VAR) {
+        Ok(token) => token,
+        Err(_) => fs::read_to_string(session_file)
+            .map_err(|e| format!("no session token in {} and cannot read {}: {}", SESSION_VAR, session_file.display(), e))?
+    };
+    let token = token.trim();
+    if token.is_empty() { return Err("empty session token".to_string()) }
+    Ok(token.to_string())
+}
+
+pub enum Fetched {
+    Downloaded,
+    // The input (or its encrypted version) exists already
+    Exists,
+}
+
+// Download the input of a day to the given path, unless it's there already. With a key, the input is only written
+// encrypted, next to the path.
+pub fn fetch_day(base_url: &str, token: &str, key: Option<&Key>, year: u16, day: usize, path: &Path) -> Result<Fetched, String> {
+    if input::exists(path) { return Ok(Fetched::Exists) }
+    let url = format!("{}/{}/day/{}/input", base_url.trim_end_matches('/'), year, day);
+    let res = http::get(&url, &[("Cookie", &format!("session={}", token))])?;
+    match res.status {
+        200 => {},
+        404 => return Err("not available (yet)".to_string()),
+        400 | 500 => return Err(format!("HTTP {}, is the session token valid?", res.status)),
+        status => return Err(format!("HTTP {}", status))
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let (path, data) = match key {
+        Some(key) => (input::encrypted(path), crypto::encrypt(key, res.body.as_bytes()).map_err(|e| e.to_string())?),
+        None => (path.to_path_buf(), res.body.into_bytes())
+    };
+    fs::write(&path, data).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    Ok(Fetched::Downloaded)
+}
+
+// The days of a year unlocked by the given time, in seconds since the epoch
+fn released(year: u16, now: i64) -> usize {
+    (1..=25).take_while(|&d| release(year, d) <= now).count()
+}
+
+// The key to encrypt the downloads with, if one is configured
+fn key(opts: &Options) -> Option<Key> {
+    if env::var_os(crypto::KEY_VAR).is_none() && !opts.key_file.exists() { return None }
+    Some(crypto::load_key(&opts.key_file).unwrap_or_else(|e| usage_error(&e)))
+}
+
+// Download the missing inputs of the selected years and days, or all the released days
+pub fn fetch(opts: &Options) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |t| t.as_secs() as i64);
+    let key = key(opts);
+    let mut token = None;
+    let mut last = None;
+    for year in selected_years(opts) {
+        let dir = opts.input_dir(year.year);
+        for d in (1..=released(year.year, now)).filter(|d| opts.days.is_empty() || opts.days.contains(d)) {
+            let path = dir.join(format!("{:02}.txt", d));
+            if input::exists(&path) { continue }
+            // Only ask for a token when there is something to download
+            let token = token.get_or_insert_with(|| session(&opts.session_file).unwrap_or_else(|e| usage_error(&e)));
+            if let Some(wait) = last.map(|last: Instant| THROTTLE.saturating_sub(last.elapsed())) {
+                thread::sleep(wait);
+            }
+            last = Some(Instant::now());
+            match fetch_day(&opts.base_url, token, key.as_ref(), year.year, d, &path) {
+                Ok(_) => println!("y{:04} d{:02} {}", year.year, d, path.display()),
+                Err(e) => println!("{}[ERR] y{:04} d{:02}     {}{}", c(colour::RED), year.year, d, e, c(colour::RESET))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::test_server::{self, Request};
+
+    fn aoc(req: &Request) -> (u16, String) {
+        match (req.path.as_str(), req.header("Cookie")) {
+            (_, Some(cookie)) if cookie != "session=s3cr3t" => (400, "Puzzle inputs differ by user.".to_string()),
+            ("/2020/day/1/input", _) => (200, "1721\n979\n".to_string()),
+            _ => (404, "Not Found".to_string())
+        }
+    }
+
+    #[test]
+    fn fetch_from_stand_in() {
+        let (url, log) = test_server::start(aoc);
+        let dir = env::temp_dir().join(format!("aoc-fetch-test-{}", std::process::id()));
+        let path = dir.join("01.txt");
+
+        assert!(matches!(fetch_day(&url, "s3cr3t", None, 2020, 1, &path), Ok(Fetched::Downloaded)));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1721\n979\n");
+        // Never downloaded again
+        assert!(matches!(fetch_day(&url, "s3cr3t", None, 2020, 1, &path), Ok(Fetched::Exists)));
+        assert!(fetch_day(&url, "s3cr3t", None, 2020, 2, &dir.join("02.txt")).is_err());
+        assert!(fetch_day(&url, "wrong", None, 2020, 3, &dir.join("03.txt")).is_err());
+        assert!(!dir.join("02.txt").exists());
+
+        // With a key only the encrypted input is written
+        let key = [7; 32];
+        let path = dir.join("sub").join("01.txt");
+        assert!(matches!(fetch_day(&url, "s3cr3t", Some(&key), 2020, 1, &path), Ok(Fetched::Downloaded)));
+        assert!(!path.exists());
+        assert_eq!(crypto::decrypt(&key, &fs::read(input::encrypted(&path)).unwrap()).unwrap(), b"1721\n979\n");
+        assert!(matches!(fetch_day(&url, "s3cr3t", Some(&key), 2020, 1, &path), Ok(Fetched::Exists)));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 4);
+        assert_eq!(log[0].method, "GET");
+        assert_eq!(log[0].path, "/2020/day/1/input");
+        assert_eq!(log[0].header("Cookie"), Some("session=s3cr3t"));
+        assert_eq!(log[0].header("User-Agent"), Some(http::USER_AGENT));
+    }
+
+    #[test]
+    fn released_days() {
+        let d01 = release(2020, 1);
+        assert_eq!(released(2020, d01 - 1), 0);
+        assert_eq!(released(2020, d01), 1);
+        assert_eq!(released(2020, d01 + 86400 * 10 - 1), 10);
+        assert_eq!(released(2020, d01 + 86400 * 365), 25);
+    }
+}
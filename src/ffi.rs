@@ -0,0 +1,222 @@
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::{ptr, slice, str};
+use crate::{normalise, OwnedDay};
+
+// The C API of the solutions. The header in include/adventofcode2020.h is generated from the declarations and the
+// /// comments of this file: keep each declaration on a single line, and run `cargo run -- c-header` after changing them.
+
+// A parsed day with its own copy of the input. The days share their parsed input between the parts with Rc, so a day
+// is not Send: C must keep it on the thread that parsed it.
+pub struct AocDay(OwnedDay);
+
+/// Write the years with solutions into `years`, at most `len` of them.
+/// Returns the number of years, which may be more than `len`.
+///
+/// # Safety
+/// `years` must point to `len` writable elements.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_years(years: *mut u16, len: usize) -> usize {
+    let all = crate::years();
+    for (i, year) in all.iter().take(len).enumerate() {
+        *years.add(i) = year.year;
+    }
+    all.len()
+}
+
+/// The number of days with solutions in a year, 0 if the year has none.
+#[no_mangle]
+pub extern "C" fn aoc_days(year: u16) -> usize {
+    crate::years().iter().find(|y| y.year == year).map_or(0, |y| y.day_parsers.len())
+}
+
+/// Parse the input of a day (counted from 1). The input is `len` bytes of UTF-8 text without a NUL terminator,
+/// normalised the same way as the inputs of the runner. Returns NULL if there is no such day, or the input is not
+/// UTF-8 or cannot be parsed. The day must be released with `aoc_free`.
+/// The day may only be used and released on the thread that parsed it, other threads must parse their own.
+///
+/// # Safety
+/// `input` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_parse(year: u16, day: usize, input: *const c_char, len: usize) -> *mut AocDay {
+    let parser = match crate::years().iter().find(|y| y.year == year).and_then(|y| y.day_parsers.get(day.wrapping_sub(1))) {
+        Some(parser) => *parser,
+        None => return ptr::null_mut()
+    };
+    let bytes = if len == 0 { &[][..] } else { slice::from_raw_parts(input as *const u8, len) };
+    let text = match str::from_utf8(bytes) {
+        Ok(text) => normalise(text.to_string()),
+        Err(_) => return ptr::null_mut()
+    };
+    // The days panic on bad inputs, which must not unwind into C
+    match panic::catch_unwind(AssertUnwindSafe(|| OwnedDay::parse(parser, text))) {
+        Ok(day) => Box::into_raw(Box::new(AocDay(day))),
+        Err(_) => ptr::null_mut()
+    }
+}
+
+/// The number of parts of a parsed day.
+///
+/// # Safety
+/// `day` must be a day returned by `aoc_parse` on the same thread and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_parts(day: *const AocDay) -> usize {
+    (*day).0.parts().len()
+}
+
+/// Solve a part (counted from 1) of a parsed day and write the answer into `buf` as a NUL-terminated decimal number,
+/// truncated to `len` bytes. Returns the length of the whole answer without the NUL like snprintf,
+/// or -1 if there is no such part or solving it failed.
+///
+/// # Safety
+/// `day` must be a day returned by `aoc_parse` on the same thread and not released yet, `buf` must point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_solve(day: *const AocDay, part: usize, buf: *mut c_char, len: usize) -> i32 {
+    let part = match (*day).0.parts().get(part.wrapping_sub(1)) {
+        Some(part) => part,
+        None => return -1
+    };
+    let answer = match panic::catch_unwind(AssertUnwindSafe(|| part.solve())) {
+        Ok(answer) => answer.to_string(),
+        Err(_) => return -1
+    };
+    if len > 0 {
+        let n = answer.len().min(len - 1);
+        ptr::copy_nonoverlapping(answer.as_ptr(), buf as *mut u8, n);
+        *buf.add(n) = 0;
+    }
+    answer.len() as i32
+}
+
+/// Release a parsed day, NULL is ignored.
+///
+/// # Safety
+/// `day` must be NULL or a day returned by `aoc_parse` on the same thread and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_free(day: *mut AocDay) {
+    if !day.is_null() {
+        drop(Box::from_raw(day));
+    }
+}
+
+const HEADER_START: &str = "\
+/* Generated from src/ffi.rs with `cargo run -- c-header`, do not edit. */
+
+#ifndef ADVENTOFCODE2020_H
+#define ADVENTOFCODE2020_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+
+/* A parsed day, which may only be used and released on the thread that parsed it */
+typedef struct aoc_day aoc_day;
+";
+
+const HEADER_END: &str = "
+#ifdef __cplusplus
+}
+#endif
+
+#endif
+";
+
+fn c_type(rust: &str) -> &'static str {
+    match rust {
+        "u16" => "uint16_t",
+        "i32" => "int",
+        "usize" => "size_t",
+        "*mut u16" => "uint16_t *",
+        "*const c_char" => "const char *",
+        "*mut c_char" => "char *",
+        "*const AocDay" => "const aoc_day *",
+        "*mut AocDay" => "aoc_day *",
+        _ => panic!("no C type for {}", rust)
+    }
+}
+
+// Declare a C function: the pointer types end with a "*" already
+fn c_declaration(c_type: &str, name: &str) -> String {
+    if c_type.ends_with('*') { format!("{}{}", c_type, name) } else { format!("{} {}", c_type, name) }
+}
+
+// The C header of the API, from the exported functions of this file and their comments
+pub fn header() -> String {
+    let mut header = HEADER_START.to_string();
+    let mut comment = Vec::new();
+    for line in include_str!("ffi.rs").lines() {
+        if let Some(doc) = line.strip_prefix("///") {
+            comment.push(doc.trim_start());
+            continue
+        }
+        let signature = match line.strip_prefix("pub unsafe extern \"C\" fn ").or_else(|| line.strip_prefix("pub extern \"C\" fn ")) {
+            Some(signature) => signature.trim_end_matches(" {"),
+            None => {
+                if !line.starts_with("#[") { comment.clear() }
+                continue
+            }
+        };
+        let (name, rest) = signature.split_once('(').unwrap();
+        let (args, ret) = rest.split_once(')').unwrap();
+        let args: Vec<String> = args.split(", ").filter(|a| !a.is_empty()).map(|arg| {
+            let (name, rust) = arg.split_once(": ").unwrap();
+            c_declaration(c_type(rust), name)
+        }).collect();
+        let ret = ret.strip_prefix(" -> ").map_or("void", c_type);
+
+        header.push_str("\n/*\n");
+        for line in comment.drain(..) {
+            header.push_str(format!(" * {}", line).trim_end());
+            header.push('\n');
+        }
+        header.push_str(" */\n");
+        header.push_str(&format!("{}({});\n", c_declaration(ret, name), args.join(", ")));
+    }
+    header + HEADER_END
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn header_is_current() {
+        assert_eq!(header(), include_str!("../include/adventofcode2020.h"), "run `cargo run -- c-header`");
+    }
+
+    #[test]
+    fn solve_example() {
+        unsafe {
+            let mut years = [0; 4];
+            assert_eq!(aoc_years(years.as_mut_ptr(), years.len()), 1);
+            assert_eq!(years[0], 2020);
+            assert_eq!(aoc_days(2020), 15);
+            assert_eq!(aoc_days(2019), 0);
+
+            let input = "1721\r\n979\r\n366\r\n299\r\n675\r\n1456\r\n";
+            let day = aoc_parse(2020, 1, input.as_ptr() as *const c_char, input.len());
+            assert!(!day.is_null());
+            assert_eq!(aoc_parts(day), 2);
+            let mut buf = [0 as c_char; 16];
+            assert_eq!(aoc_solve(day, 1, buf.as_mut_ptr(), buf.len()), 6);
+            assert_eq!(CStr::from_ptr(buf.as_ptr()).to_str(), Ok("514579"));
+            // Truncated like snprintf
+            assert_eq!(aoc_solve(day, 2, buf.as_mut_ptr(), 4), 9);
+            assert_eq!(CStr::from_ptr(buf.as_ptr()).to_str(), Ok("241"));
+            assert_eq!(aoc_solve(day, 3, buf.as_mut_ptr(), buf.len()), -1);
+            aoc_free(day);
+
+            assert!(aoc_parse(2020, 16, input.as_ptr() as *const c_char, input.len()).is_null());
+            assert!(aoc_parse(2020, 15, "x".as_ptr() as *const c_char, 1).is_null());
+            // A panic while solving is an error too
+            let day = aoc_parse(2020, 1, "1".as_ptr() as *const c_char, 1);
+            assert_eq!(aoc_solve(day, 1, buf.as_mut_ptr(), buf.len()), -1);
+            aoc_free(day);
+            aoc_free(ptr::null_mut());
+        }
+    }
+}
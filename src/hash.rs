@@ -0,0 +1,9 @@
+// 64 bit FNV-1a hash, stable across builds and platforms (unlike std's DefaultHasher)
+pub fn fnv1a(data: &[u8]) -> u64 {
+    let mut h = 0xcbf29ce484222325u64;
+    for &b in data {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    h
+}
@@ -0,0 +1,243 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+// A minimal HTTP/1.1 client: plain HTTP is spoken over a TcpStream, HTTPS is left to curl, as std has no TLS
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// AoC asks automated tools to identify themselves
+pub const USER_AGENT: &str = concat!("github.com/dszoboszlay/adventofcode2020 by ", env!("CARGO_PKG_AUTHORS"));
+
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+pub fn get(url: &str, headers: &[(&str, &str)]) -> Result<Response, String> {
+    request("GET", url, headers, None)
+}
+
+pub fn post(url: &str, headers: &[(&str, &str)], body: &str) -> Result<Response, String> {
+    request("POST", url, headers, Some(body))
+}
+
+fn request(method: &str, url: &str, headers: &[(&str, &str)], body: Option<&str>) -> Result<Response, String> {
+    if url.starts_with("https://") {
+        curl(method, url, headers, body)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/")
+        };
+        plain(method, host, path, headers, body).map_err(|e| format!("{}: {}", url, e))
+    } else {
+        Err(format!("unsupported URL: {}", url))
+    }
+}
+
+fn plain(method: &str, host: &str, path: &str, headers: &[(&str, &str)], body: Option<&str>) -> Result<Response, String> {
+    let addr = if host.contains(':') { host.to_string() } else { format!("{}:80", host) };
+    let mut stream = TcpStream::connect(&addr).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(TIMEOUT)).map_err(|e| e.to_string())?;
+
+    let mut req = format!("{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nConnection: close\r\n", method, path, host, USER_AGENT);
+    for (name, value) in headers {
+        req.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if let Some(body) = body {
+        req.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    req.push_str("\r\n");
+    req.push_str(body.unwrap_or(""));
+    stream.write_all(req.as_bytes()).map_err(|e| e.to_string())?;
+
+    // The server closes the connection after the response
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).map_err(|e| e.to_string())?;
+    parse_response(&raw)
+}
+
+fn parse_response(raw: &[u8]) -> Result<Response, String> {
+    let end = raw.windows(4).position(|w| w == b"\r\n\r\n").ok_or("incomplete response")?;
+    let head = String::from_utf8_lossy(&raw[..end]);
+    let mut lines = head.split("\r\n");
+    let status = lines.next().and_then(|l| l.split_whitespace().nth(1)).and_then(|s| s.parse().ok())
+        .ok_or("bad status line")?;
+    let chunked = lines.any(|l| {
+        let l = l.to_ascii_lowercase();
+        l.starts_with("transfer-encoding:") && l.contains("chunked")
+    });
+    let body = &raw[end + 4..];
+    let body = if chunked { dechunk(body)? } else { body.to_vec() };
+    Ok(Response { status, body: String::from_utf8(body).map_err(|e| e.to_string())? })
+}
+
+fn dechunk(mut data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let eol = data.windows(2).position(|w| w == b"\r\n").ok_or("bad chunk")?;
+        let size = String::from_utf8_lossy(&data[..eol]);
+        let size = usize::from_str_radix(size.split(';').next().unwrap().trim(), 16).map_err(|_| "bad chunk size")?;
+        if size == 0 { return Ok(body) }
+        let chunk = data.get(eol + 2..eol + 2 + size).ok_or("truncated chunk")?;
+        body.extend_from_slice(chunk);
+        data = data.get(eol + 4 + size..).ok_or("truncated chunk")?;
+    }
+}
+
+// The headers in curl's config file format
+fn curl_config(headers: &[(&str, &str)]) -> String {
+    headers.iter().map(|(name, value)| {
+        let header = format!("{}: {}", name, value).replace('\\', "\\\\").replace('"', "\\\"");
+        format!("header = \"{}\"\n", header)
+    }).collect()
+}
+
+// A config file only the current user can read, removed when dropped
+struct ConfigFile(PathBuf);
+
+impl ConfigFile {
+    fn create(content: &str) -> Result<ConfigFile, String> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir().join(format!("aoc-curl-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::Relaxed)));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = ConfigFile(path);
+        options.open(&file.0).and_then(|mut f| f.write_all(content.as_bytes()))
+            .map_err(|e| format!("cannot write {}: {}", file.0.display(), e))?;
+        Ok(file)
+    }
+}
+
+impl Drop for ConfigFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+// The headers (like the session cookie) are passed in a config file: the command line is visible to every user
+fn curl_command(method: &str, url: &str, config: &Path, body: bool) -> Command {
+    let mut cmd = Command::new("curl");
+    cmd.args(["--silent", "--show-error", "--max-time", &TIMEOUT.as_secs().to_string(), "--request", method,
+              "--user-agent", USER_AGENT, "--write-out", "\n%{http_code}"]);
+    cmd.arg("--config").arg(config);
+    if body {
+        cmd.args(["--data-binary", "@-"]);
+    }
+    cmd.arg(url);
+    cmd
+}
+
+// curl prints the status code on a last line of its own after the body
+fn curl(method: &str, url: &str, headers: &[(&str, &str)], body: Option<&str>) -> Result<Response, String> {
+    let config = ConfigFile::create(&curl_config(headers))?;
+    let mut child = curl_command(method, url, &config.0, body.is_some())
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()
+        .map_err(|e| format!("cannot run curl: {}", e))?;
+    child.stdin.take().unwrap().write_all(body.unwrap_or("").as_bytes()).map_err(|e| e.to_string())?;
+    let out = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        return Err(format!("{}: {}", url, String::from_utf8_lossy(&out.stderr).trim()))
+    }
+    let out = String::from_utf8(out.stdout).map_err(|e| e.to_string())?;
+    let i = out.rfind('\n').ok_or("no status from curl")?;
+    let status = out[i + 1..].trim().parse().map_err(|_| "no status from curl")?;
+    Ok(Response { status, body: out[..i].to_string() })
+}
+
+// A stand-in HTTP server for the tests of the clients
+#[cfg(test)]
+pub mod test_server {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    // The requests the server got
+    #[derive(Clone, Debug)]
+    pub struct Request {
+        pub method: String,
+        pub path: String,
+        pub headers: Vec<(String, String)>,
+        pub body: String,
+    }
+
+    impl Request {
+        pub fn header(&self, name: &str) -> Option<&str> {
+            self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+        }
+    }
+
+    // Serve responses from the handler on a random local port, returns the base URL and the log of requests
+    pub fn start(handler: fn(&Request) -> (u16, String)) -> (String, Arc<Mutex<Vec<Request>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let server_log = log.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut fields = line.split_whitespace();
+                let (method, path) = (fields.next().unwrap().to_string(), fields.next().unwrap().to_string());
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() { break }
+                    let i = line.find(':').unwrap();
+                    headers.push((line[..i].to_string(), line[i + 1..].trim().to_string()));
+                }
+                let mut req = Request { method, path, headers, body: String::new() };
+                let len = req.header("Content-Length").map_or(0, |l| l.parse().unwrap());
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body).unwrap();
+                req.body = String::from_utf8(body).unwrap();
+
+                let (status, body) = handler(&req);
+                server_log.lock().unwrap().push(req);
+                write!(stream, "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body).unwrap();
+            }
+        });
+        (url, log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunked_response() {
+        let res = parse_response(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n1721\r\n5;x=y\r\n\n979\n\r\n0\r\n\r\n").unwrap();
+        assert_eq!(res.status, 200);
+        assert_eq!(res.body, "1721\n979\n");
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\n").is_err());
+    }
+
+    #[test]
+    fn curl_keeps_headers_off_the_command_line() {
+        let headers = [("Cookie", "session=s3cr3t"), ("X-Quoted", "a \"b\" c\\d")];
+        let config = ConfigFile::create(&curl_config(&headers)).unwrap();
+        let cmd = curl_command("POST", "https://adventofcode.com/2020/day/1/answer", &config.0, true);
+        assert!(cmd.get_args().all(|arg| !arg.to_string_lossy().contains("s3cr3t")));
+        assert_eq!(fs::read_to_string(&config.0).unwrap(), "header = \"Cookie: session=s3cr3t\"\nheader = \"X-Quoted: a \\\"b\\\" c\\\\d\"\n");
+        #[cfg(unix)]
+        assert_eq!(std::os::unix::fs::PermissionsExt::mode(&fs::metadata(&config.0).unwrap().permissions()) & 0o777, 0o600);
+        let path = config.0.clone();
+        drop(config);
+        assert!(!path.exists());
+    }
+}
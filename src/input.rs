@@ -0,0 +1,73 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::crypto;
+
+pub use adventofcode2020::normalise;
+
+// Inputs may be stored encrypted in a file with an .enc suffix instead, like input/01.txt.enc
+pub fn encrypted(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".enc");
+    PathBuf::from(name)
+}
+
+pub fn exists(path: &Path) -> bool {
+    path.is_file() || encrypted(path).is_file()
+}
+
+// Read an input file, or decrypt it if only the encrypted one exists, and normalise it
+pub fn read(path: &Path, key_file: &Path) -> io::Result<String> {
+    if path.is_file() {
+        return fs::read_to_string(path).map(normalise)
+    }
+    let data = fs::read(encrypted(path))?;
+    let key = crypto::load_key(key_file).map_err(io::Error::other)?;
+    let plain = crypto::decrypt(&key, &data).map_err(io::Error::other)?;
+    String::from_utf8(plain).map(normalise).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{solutions, years, Options};
+
+    // The ways an input may be saved
+    fn variants(lf: &str) -> Vec<(&'static str, String)> {
+        let lf = lf.trim_end_matches('\n').to_string() + "\n";
+        vec![("LF", lf.clone()),
+             ("CRLF", lf.replace('\n', "\r\n")),
+             ("BOM", format!("\u{feff}{}", lf)),
+             ("no final newline", lf.trim_end().to_string()),
+             ("trailing whitespace", lf.replace('\n', " \t\n") + "\n\n")]
+    }
+
+    #[test]
+    fn normalise_variants() {
+        for (name, variant) in variants("a b\n\nc\n") {
+            assert_eq!(normalise(variant), "a b\n\nc", "{}", name);
+        }
+        assert_eq!(normalise(String::new()), "");
+        assert_eq!(normalise(" \n\r\n".to_string()), "");
+    }
+
+    #[test]
+    fn every_day_on_variants() {
+        let opts = Options::default();
+        for year in years() {
+            let dir = opts.input_dir(year.year);
+            let sols = solutions(&dir.join("solutions.txt"));
+            for (d, (parser, expected)) in year.day_parsers.iter().zip(sols.iter()).enumerate() {
+                let path = dir.join(format!("{:02}.txt", d + 1));
+                if !path.is_file() { continue }
+                for (name, variant) in variants(&fs::read_to_string(&path).unwrap()) {
+                    let input = normalise(variant);
+                    let day = parser(&input);
+                    for (part, expected) in day.parts.iter().zip(expected) {
+                        assert!(expected.matches(part.solve()), "y{:04} d{:02} {}", year.year, d + 1, name);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use crate::spoiler;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    Correct,
+    Wrong,
+    TooHigh,
+    TooLow,
+}
+
+impl Verdict {
+    pub fn parse(s: &str) -> Option<Verdict> {
+        match s {
+            "correct" => Some(Verdict::Correct),
+            "wrong" => Some(Verdict::Wrong),
+            "high" => Some(Verdict::TooHigh),
+            "low" => Some(Verdict::TooLow),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "high",
+            Verdict::TooLow => "low"
+        }
+    }
+}
+
+// A guess submitted to AoC and the verdict it got
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    pub day: usize,
+    pub part: usize,
+    pub guess: i64,
+    pub verdict: Verdict,
+}
+
+// The journal of a year is kept next to its solutions, one "DAY PART GUESS VERDICT" line per guess
+pub const JOURNAL_FILE: &str = "journal.txt";
+
+pub struct Journal {
+    pub entries: Vec<Entry>,
+}
+
+impl Journal {
+    pub fn load(from: &Path) -> Journal {
+        let mut entries = Vec::new();
+        if from.is_file() {
+            for line in fs::read_to_string(from).unwrap().lines() {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() != 4 { continue }
+                if let (Ok(day), Ok(part), Ok(guess), Some(verdict)) =
+                    (fields[0].parse(), fields[1].parse(), fields[2].parse(), Verdict::parse(fields[3])) {
+                    entries.push(Entry { day, part, guess, verdict });
+                }
+            }
+        }
+        Journal { entries }
+    }
+
+    pub fn append(to: &Path, entry: &Entry) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(to)?;
+        writeln!(file, "{} {} {} {}", entry.day, entry.part, entry.guess, entry.verdict.name())
+    }
+
+    // Known problems with an answer before submitting it
+    pub fn check(&self, day: usize, part: usize, answer: i64) -> Option<String> {
+        let entries = || self.entries.iter().filter(|e| e.day == day && e.part == part);
+        if let Some(e) = entries().find(|e| e.verdict == Verdict::Correct) {
+            return if e.guess == answer { None } else { Some(format!("the correct answer is known to be {}", spoiler::answer(e.guess))) }
+        }
+        if entries().any(|e| e.guess == answer) {
+            return Some("this answer was already rejected".to_string())
+        }
+        if let Some(high) = entries().filter(|e| e.verdict == Verdict::TooHigh).map(|e| e.guess).min() {
+            if answer >= high { return Some(format!("{} was already too high", spoiler::answer(high))) }
+        }
+        if let Some(low) = entries().filter(|e| e.verdict == Verdict::TooLow).map(|e| e.guess).max() {
+            if answer <= low { return Some(format!("{} was already too low", spoiler::answer(low))) }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_against_bounds() {
+        let entry = |guess, verdict| Entry { day: 1, part: 2, guess, verdict };
+        let mut journal = Journal { entries: vec![entry(100, Verdict::TooHigh), entry(10, Verdict::TooLow), entry(50, Verdict::Wrong)] };
+        assert_eq!(journal.check(1, 2, 42), None);
+        assert_eq!(journal.check(1, 1, 100), None);
+        assert!(journal.check(1, 2, 50).is_some());
+        assert!(journal.check(1, 2, 120).is_some());
+        assert!(journal.check(1, 2, 10).is_some());
+        journal.entries.push(entry(42, Verdict::Correct));
+        assert_eq!(journal.check(1, 2, 42), None);
+        assert!(journal.check(1, 2, 43).is_some());
+    }
+}
@@ -0,0 +1,290 @@
+use std::fmt;
+
+// Just enough JSON for the tools talking to the runner, and for reading the exports of AoC
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    // Numbers with a fraction or an exponent, or too large for an Int
+    Float(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+
+    pub fn parse(s: &str) -> Result<Json, String> {
+        let mut parser = Parser { s, i: 0 };
+        let json = parser.value(0)?;
+        parser.whitespace();
+        if parser.i < s.len() { return Err(parser.error("trailing characters")) }
+        Ok(json)
+    }
+
+    // A field of an object
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Json::Int(i) => Some(*i),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(s) => Some(s),
+            _ => None
+        }
+    }
+}
+
+// Nesting deeper than this is refused instead of overflowing the stack
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    s: &'a str,
+    // The byte offset of the next character
+    i: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, what: &str) -> String {
+        format!("{} at offset {}", what, self.i)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.s.as_bytes().get(self.i).copied()
+    }
+
+    fn whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.i += 1
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if !self.s[self.i..].starts_with(token) { return Err(self.error(&format!("expected {}", token))) }
+        self.i += token.len();
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, String> {
+        if depth > MAX_DEPTH { return Err(self.error("nested too deep")) }
+        self.whitespace();
+        match self.peek() {
+            Some(b'n') => self.expect("null").map(|_| Json::Null),
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::Str),
+            Some(b'[') => {
+                self.i += 1;
+                let mut items = Vec::new();
+                self.whitespace();
+                if self.peek() == Some(b']') {
+                    self.i += 1;
+                    return Ok(Json::Array(items))
+                }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    self.whitespace();
+                    match self.peek() {
+                        Some(b',') => self.i += 1,
+                        Some(b']') => {
+                            self.i += 1;
+                            return Ok(Json::Array(items))
+                        },
+                        _ => return Err(self.error("expected , or ]"))
+                    }
+                }
+            },
+            Some(b'{') => {
+                self.i += 1;
+                let mut fields = Vec::new();
+                self.whitespace();
+                if self.peek() == Some(b'}') {
+                    self.i += 1;
+                    return Ok(Json::Object(fields))
+                }
+                loop {
+                    self.whitespace();
+                    if self.peek() != Some(b'"') { return Err(self.error("expected a key")) }
+                    let key = self.string()?;
+                    self.whitespace();
+                    self.expect(":")?;
+                    fields.push((key, self.value(depth + 1)?));
+                    self.whitespace();
+                    match self.peek() {
+                        Some(b',') => self.i += 1,
+                        Some(b'}') => {
+                            self.i += 1;
+                            return Ok(Json::Object(fields))
+                        },
+                        _ => return Err(self.error("expected , or }"))
+                    }
+                }
+            },
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end"))
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.i;
+        let mut float = false;
+        while let Some(b) = self.peek() {
+            match b {
+                b'0'..=b'9' | b'-' | b'+' => {},
+                b'.' | b'e' | b'E' => float = true,
+                _ => break
+            }
+            self.i += 1
+        }
+        let n = &self.s[start..self.i];
+        let err = || format!("bad number {} at offset {}", n, start);
+        // Rust accepts a few more forms than JSON: leading zeros and dots without digits after them
+        let digits = n.strip_prefix('-').unwrap_or(n);
+        if !digits.starts_with(|c: char| c.is_ascii_digit()) || (digits.starts_with('0') && digits[1..].starts_with(|c: char| c.is_ascii_digit())) ||
+           n.split('.').skip(1).any(|fraction| !fraction.starts_with(|c: char| c.is_ascii_digit())) {
+            return Err(err())
+        }
+        match n.parse() {
+            Ok(i) if !float => Ok(Json::Int(i)),
+            _ => n.parse().map(Json::Float).map_err(|_| err())
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let hex = self.s.get(self.i..self.i + 4).filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit())).ok_or_else(|| self.error("bad \\u escape"))?;
+        self.i += 4;
+        Ok(u32::from_str_radix(hex, 16).unwrap())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.i += 1;
+        let mut s = String::new();
+        loop {
+            // Copy everything up to the next quote or escape as it is
+            let rest = &self.s[self.i..];
+            let end = rest.find(['"', '\\']).ok_or_else(|| self.error("unterminated string"))?;
+            if rest[..end].contains(|c: char| (c as u32) < 0x20) { return Err(self.error("control character in a string")) }
+            s.push_str(&rest[..end]);
+            self.i += end + 1;
+            if rest.as_bytes()[end] == b'"' { return Ok(s) }
+            let c = match self.peek() {
+                Some(b'"') => '"',
+                Some(b'\\') => '\\',
+                Some(b'/') => '/',
+                Some(b'b') => '\u{8}',
+                Some(b'f') => '\u{c}',
+                Some(b'n') => '\n',
+                Some(b'r') => '\r',
+                Some(b't') => '\t',
+                Some(b'u') => {
+                    self.i += 1;
+                    let mut code = self.hex4()?;
+                    // Characters outside the BMP are escaped as surrogate pairs
+                    if (0xd800..0xdc00).contains(&code) && self.s[self.i..].starts_with("\\u") {
+                        self.i += 2;
+                        let low = self.hex4()?;
+                        if !(0xdc00..0xe000).contains(&low) { return Err(self.error("bad surrogate pair")) }
+                        code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                    }
+                    s.push(char::from_u32(code).ok_or_else(|| self.error("bad \\u escape"))?);
+                    continue
+                },
+                _ => return Err(self.error("bad escape"))
+            };
+            s.push(c);
+            self.i += 1;
+        }
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Int(i) => write!(f, "{}", i),
+            Json::Float(x) if x.is_finite() => write!(f, "{:?}", x),
+            Json::Float(_) => write!(f, "null"),
+            Json::Str(s) => write_str(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 { write!(f, ",")? }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 { write!(f, ",")? }
+                    write_str(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write() {
+        let json = Json::object(vec![("year", Json::Int(2020)),
+                                     ("days", Json::Array(vec![Json::Int(1), Json::Int(-2)])),
+                                     ("error", Json::Str("bad \"input\"\n\u{1}".to_string()))]);
+        assert_eq!(json.to_string(), r#"{"year":2020,"days":[1,-2],"error":"bad \"input\"\n\u0001"}"#);
+    }
+
+    #[test]
+    fn parse() {
+        let s = r#" {"a": [1, -2.5e1, 1e400, true, false, null, {}, []], "b": "x\"\\\/\n\u00e9\ud83c\udf84é", "c": 12345678901234567890} "#;
+        let json = Json::parse(s).unwrap();
+        assert_eq!(json.get("a"), Some(&Json::Array(vec![Json::Int(1), Json::Float(-25.0), Json::Float(f64::INFINITY), Json::Bool(true),
+                                                          Json::Bool(false), Json::Null, Json::Object(Vec::new()), Json::Array(Vec::new())])));
+        assert_eq!(json.get("b").and_then(Json::as_str), Some("x\"\\/\né🎄é"));
+        assert_eq!(json.get("c"), Some(&Json::Float(12345678901234567890.0)));
+        assert_eq!(json.get("d"), None);
+        // What is written can be read back
+        let written = Json::object(vec![("x", Json::Str("\u{1}\t".to_string())), ("y", Json::Float(0.5)), ("z", Json::Null)]);
+        assert_eq!(Json::parse(&written.to_string()), Ok(written));
+        for bad in ["", "[1,]", "{\"a\" 1}", "\"abc", "01", "1.", "-", "[1] 2", "\"\\x\"", "\"\\u12\"", "tru", "\"a\nb\""] {
+            assert!(Json::parse(bad).is_err(), "{}", bad);
+        }
+        assert!(Json::parse(&"[".repeat(1000)).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use adventofcode2020::years;
+use crate::colour::{self, c};
+use crate::json::Json;
+use crate::usage_error;
+
+// A member of a private leaderboard and when they got their stars, by day and part
+struct Member {
+    name: String,
+    stars: BTreeMap<(usize, usize), i64>,
+}
+
+struct Board {
+    year: u16,
+    members: Vec<Member>,
+}
+
+// The timestamps are numbers in the current exports, and were strings in older ones
+fn timestamp(json: &Json) -> Option<i64> {
+    json.as_i64().or_else(|| json.as_str()?.parse().ok())
+}
+
+// The board from the JSON export of a private leaderboard
+fn load(json: &Json) -> Result<Board, String> {
+    let year = json.get("event").and_then(|e| e.as_str()?.parse().ok()).ok_or("no event year")?;
+    let members = match json.get("members") {
+        Some(Json::Object(members)) => members,
+        _ => return Err("no members".to_string())
+    };
+    let mut board = Board { year, members: Vec::new() };
+    for (id, member) in members {
+        let name = match member.get("name") {
+            Some(Json::Str(name)) => name.clone(),
+            _ => format!("(anonymous user #{})", id)
+        };
+        let mut stars = BTreeMap::new();
+        if let Some(Json::Object(days)) = member.get("completion_day_level") {
+            for (day, parts) in days {
+                let day = day.parse().map_err(|_| format!("bad day {} of {}", day, name))?;
+                let parts = match parts {
+                    Json::Object(parts) => parts,
+                    _ => return Err(format!("bad day {} of {}", day, name))
+                };
+                for (part, star) in parts {
+                    let part = part.parse().map_err(|_| format!("bad part {} of {}", part, name))?;
+                    let ts = star.get("get_star_ts").and_then(timestamp).ok_or_else(|| format!("no star time for d{:02} p{} of {}", day, part, name))?;
+                    stars.insert((day, part), ts);
+                }
+            }
+        }
+        board.members.push(Member { name, stars });
+    }
+    Ok(board)
+}
+
+// Days since 1970-01-01 of a date of the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// The puzzles unlock at midnight EST (UTC-5)
+pub fn release(year: u16, day: usize) -> i64 {
+    days_from_civil(year as i64, 12, day as i64) * 86400 + 5 * 3600
+}
+
+fn hms(secs: i64) -> String {
+    let secs = secs.max(0);
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+// How long a member took for a star since the puzzle unlocked
+fn time(board: &Board, member: &Member, day: usize, part: usize) -> Option<i64> {
+    member.stars.get(&(day, part)).map(|ts| ts - release(board.year, day))
+}
+
+// AoC's local score: for each star, a point for every member who got it later (or didn't get it)
+fn local_scores(board: &Board) -> Vec<i64> {
+    let n = board.members.len() as i64;
+    let mut scores = vec![0; board.members.len()];
+    let stars: Vec<(usize, usize)> = board.members.iter().flat_map(|m| m.stars.keys().copied()).collect();
+    for star in stars.into_iter().collect::<BTreeSet<_>>() {
+        let mut got: Vec<(i64, usize)> = board.members.iter().enumerate().filter_map(|(i, m)| Some((*m.stars.get(&star)?, i))).collect();
+        got.sort();
+        for (rank, (_, i)) in got.into_iter().enumerate() {
+            scores[i] += n - rank as i64;
+        }
+    }
+    scores
+}
+
+// The time between the two stars of the days where a member has both, the middle one of them
+fn median_delta(member: &Member) -> Option<i64> {
+    let mut deltas: Vec<i64> = member.stars.iter()
+        .filter(|((_, part), _)| *part == 2)
+        .filter_map(|(&(day, _), ts2)| Some(ts2 - member.stars.get(&(day, 1))?))
+        .collect();
+    deltas.sort();
+    deltas.get(deltas.len().wrapping_sub(1) / 2).copied()
+}
+
+// The ranks of the keys, the smallest key is the first, equal keys share a rank
+fn ranks<K: PartialOrd>(keys: &[K]) -> Vec<usize> {
+    keys.iter().map(|k| 1 + keys.iter().filter(|other| *other < k).count()).collect()
+}
+
+// A scoring scheme: its name, the value shown for each member and the ranks
+struct Scheme {
+    name: &'static str,
+    values: Vec<String>,
+    ranks: Vec<usize>,
+}
+
+fn schemes(board: &Board) -> Vec<Scheme> {
+    let members = &board.members;
+    let local = local_scores(board);
+    let stars: Vec<usize> = members.iter().map(|m| m.stars.len()).collect();
+    let last: Vec<i64> = members.iter().map(|m| m.stars.values().max().copied().unwrap_or(i64::MAX)).collect();
+    let total: Vec<i64> = members.iter().map(|m| m.stars.keys().filter_map(|&(d, p)| time(board, m, d, p)).sum()).collect();
+    let deltas: Vec<Option<i64>> = members.iter().map(median_delta).collect();
+    vec![
+        Scheme {
+            name: "local",
+            values: local.iter().map(|s| s.to_string()).collect(),
+            ranks: ranks(&local.iter().map(|s| -s).collect::<Vec<_>>()),
+        },
+        // Ties are broken by who got their last star first
+        Scheme {
+            name: "stars",
+            values: stars.iter().map(|s| s.to_string()).collect(),
+            ranks: ranks(&stars.iter().zip(&last).map(|(&s, &l)| (-(s as i64), l)).collect::<Vec<_>>()),
+        },
+        // The total time of the stars, among the members with as many stars
+        Scheme {
+            name: "time",
+            values: total.iter().map(|&t| hms(t)).collect(),
+            ranks: ranks(&stars.iter().zip(&total).map(|(&s, &t)| (-(s as i64), t)).collect::<Vec<_>>()),
+        },
+        // The median time from the first star of a day to the second one, the members without both stars of any day last
+        Scheme {
+            name: "p1→p2",
+            values: deltas.iter().map(|d| d.map_or("-".to_string(), hms)).collect(),
+            ranks: ranks(&deltas.iter().map(|d| d.unwrap_or(i64::MAX)).collect::<Vec<_>>()),
+        },
+    ]
+}
+
+// Show the star times of a private leaderboard exported to a JSON file, and the rankings by a few scoring schemes:
+// leaderboard FILE
+pub fn leaderboard(args: &[String]) {
+    let path = match args {
+        [path] => path,
+        _ => usage_error("usage: leaderboard FILE")
+    };
+    let text = fs::read_to_string(path).unwrap_or_else(|e| usage_error(&format!("cannot read {}: {}", path, e)));
+    let board = Json::parse(&text).and_then(|json| load(&json)).unwrap_or_else(|e| usage_error(&format!("{}: {}", path, e)));
+    let implemented = years().iter().find(|y| y.year == board.year).map_or(0, |y| y.day_parsers.len());
+    println!("y{:04} leaderboard of {} members, {} days implemented here", board.year, board.members.len(), implemented);
+
+    let schemes = schemes(&board);
+    let mut order: Vec<usize> = (0..board.members.len()).collect();
+    order.sort_by_key(|&i| (schemes[0].ranks[i], board.members[i].name.clone()));
+    for &i in &order {
+        let member = &board.members[i];
+        println!();
+        println!("{} ({} stars)", member.name, member.stars.len());
+        let days: Vec<usize> = member.stars.keys().map(|&(d, _)| d).collect::<BTreeSet<_>>().into_iter().collect();
+        for d in days {
+            let part = |p| time(&board, member, d, p).map_or("-".to_string(), hms);
+            let delta = match (member.stars.get(&(d, 1)), member.stars.get(&(d, 2))) {
+                (Some(t1), Some(t2)) => hms(t2 - t1),
+                _ => "-".to_string()
+            };
+            let note = if d > implemented { format!(" {}not implemented{}", c(colour::DIM), c(colour::RESET)) } else { String::new() };
+            println!("  d{:02}  p1 {:>11}  p2 {:>11}  p1→p2 {:>11}{}", d, part(1), part(2), delta, note);
+        }
+    }
+
+    println!();
+    print!("{:30}", "ranking");
+    for scheme in &schemes {
+        print!(" {:>18}", scheme.name);
+    }
+    println!();
+    for &i in &order {
+        print!("{:30}", board.members[i].name);
+        for scheme in &schemes {
+            print!(" {:>18}", format!("#{} {:>11}", scheme.ranks[i], scheme.values[i]));
+        }
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"{"event":"2020","owner_id":1,"members":{
+        "1":{"id":1,"name":"Ann","stars":4,"local_score":0,"completion_day_level":{
+            "1":{"1":{"get_star_ts":1606800060,"star_index":1},"2":{"get_star_ts":1606800120,"star_index":2}},
+            "2":{"1":{"get_star_ts":1606886500,"star_index":3},"2":{"get_star_ts":1606887000,"star_index":4}}}},
+        "2":{"id":2,"name":null,"stars":3,"completion_day_level":{
+            "1":{"1":{"get_star_ts":"1606800030"},"2":{"get_star_ts":"1606800600"}},
+            "2":{"1":{"get_star_ts":"1606886450"}}}},
+        "3":{"id":3,"name":"Cy","stars":0,"completion_day_level":{}}}}"#;
+
+    #[test]
+    fn release_times() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(release(2020, 1), 1606798800);
+        assert_eq!(release(2020, 25), 1606798800 + 24 * 86400);
+        assert_eq!(hms(3 * 3600 + 62), "3:01:02");
+        assert_eq!(hms(30 * 3600), "30:00:00");
+    }
+
+    #[test]
+    fn scores() {
+        let board = load(&Json::parse(EXPORT).unwrap()).unwrap();
+        assert_eq!(board.year, 2020);
+        let names: Vec<&str> = board.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["Ann", "(anonymous user #2)", "Cy"]);
+        assert_eq!(time(&board, &board.members[1], 1, 1), Some(1230));
+        // d01 p1: 2nd and 1st, d01 p2: 1st and 2nd, d02 p1: 2nd and 1st, d02 p2: Ann only
+        assert_eq!(local_scores(&board), [2 + 3 + 2 + 3, 3 + 2 + 3, 0]);
+
+        let schemes = schemes(&board);
+        assert_eq!(schemes.iter().map(|s| s.name).collect::<Vec<_>>(), ["local", "stars", "time", "p1→p2"]);
+        assert_eq!(schemes[0].ranks, [1, 2, 3]);
+        assert_eq!(schemes[1].ranks, [1, 2, 3]);
+        assert_eq!(schemes[2].ranks, [1, 2, 3]);
+        // Ann's deltas are 60 and 500, the anonymous user's only one is 570
+        assert_eq!(schemes[3].values, ["0:01:00", "0:09:30", "-"]);
+        assert_eq!(schemes[3].ranks, [1, 2, 3]);
+        assert_eq!(ranks(&[3, 1, 3, 2]), [3, 1, 3, 2]);
+
+        assert!(load(&Json::parse(r#"{"event":"2020"}"#).unwrap()).is_err());
+    }
+}
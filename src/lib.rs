@@ -0,0 +1,152 @@
+use std::mem::{self, ManuallyDrop};
+use params::Param;
+use rng::Rng;
+use vis::Visualizer;
+
+// Day parsers borrow the input: parts may keep slices of it instead of copying it,
+// so the input has to outlive the parsed day
+pub type DayParser = for<'a> fn(&'a str) -> Day<'a>;
+
+// Generators make random inputs of a given size (lines, records, ...) for profiling how the days scale,
+// or None if a day can't have an input that large
+pub type Generator = fn(usize, &mut Rng) -> Option<String>;
+
+pub struct Year {
+    pub year: u16,
+    pub day_parsers: Vec<DayParser>,
+    pub generators: Vec<Generator>,
+    // The puzzle parameters of the days that have any, by day
+    pub params: Vec<(usize, &'static Param)>,
+    pub alternates: Vec<Alternate>,
+}
+
+impl Year {
+    pub fn params(&self, day: usize) -> Vec<&'static Param> {
+        self.params.iter().filter(|(d, _)| *d == day).map(|(_, p)| *p).collect()
+    }
+
+    pub fn alternates(&self, day: usize, part: usize) -> Vec<&Alternate> {
+        self.alternates.iter().filter(|a| a.day == day && a.part == part).collect()
+    }
+}
+
+// Parses the input for a single part
+pub type PartParser = for<'a> fn(&'a str) -> Box<dyn Part + 'a>;
+
+// A second implementation of a part, like a brute force, which the part can be checked against (see `shrink --against`)
+pub struct Alternate {
+    pub day: usize,
+    pub part: usize,
+    pub name: &'static str,
+    pub parser: PartParser,
+}
+
+pub struct Day<'a> {
+    pub parts: Vec<Box<dyn Part + 'a>>,
+}
+
+// A parsed day with its own copy of the input, which the parts may borrow, for keeping days around (in the REPL or
+// behind the C API) without tying them to the lifetime of a separate input. Dropping it releases both.
+pub struct OwnedDay {
+    day: ManuallyDrop<Day<'static>>,
+    input: *mut str,
+}
+
+impl OwnedDay {
+    // Panics if the parser does, the input is released then
+    pub fn parse(parser: DayParser, input: String) -> OwnedDay {
+        struct Release(*mut str);
+        impl Drop for Release {
+            fn drop(&mut self) {
+                drop(unsafe { Box::from_raw(self.0) })
+            }
+        }
+        let input = Box::into_raw(input.into_boxed_str());
+        let release = Release(input);
+        // The input is only released after the day, see Drop
+        let day = parser(unsafe { &*input });
+        mem::forget(release);
+        OwnedDay { day: ManuallyDrop::new(day), input }
+    }
+
+    pub fn parts(&self) -> &[Box<dyn Part + '_>] {
+        &self.day.parts
+    }
+}
+
+impl Drop for OwnedDay {
+    fn drop(&mut self) {
+        // The parts go first, as they may borrow the input
+        unsafe {
+            ManuallyDrop::drop(&mut self.day);
+            drop(Box::from_raw(self.input));
+        }
+    }
+}
+
+// Key/value details about how an answer was found
+pub type Details = Vec<(&'static str, String)>;
+
+pub trait Part {
+    fn solve(&self) -> i64;
+
+    // Parts may override this to show their reasoning with --explain
+    fn explain(&self) -> Details {
+        Vec::new()
+    }
+
+    // Parts may override this to emit frames of their work with --visualize
+    fn visualize(&self, _vis: &mut dyn Visualizer) {}
+}
+
+pub mod ffi;
+pub mod log;
+pub mod params;
+pub mod progress;
+pub mod rng;
+pub mod trace;
+pub mod vis;
+pub mod y2020;
+
+pub fn years() -> Vec<Year> {
+    vec![y2020::year()]
+}
+
+// The policy for the inputs handed to the day parsers: the input is its lines joined with "\n",
+// without a byte order mark, "\r" line endings, trailing whitespace on the lines or blank lines at the end
+// (so without a final newline either). Inputs saved on Windows or copied from a browser parse the same way.
+pub fn normalise(s: String) -> String {
+    if is_normal(&s) { return s }
+    let s = s.strip_prefix('\u{feff}').unwrap_or(&s);
+    let lines: Vec<&str> = s.split('\n').map(|line| line.trim_end()).collect();
+    let end = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |i| i + 1);
+    lines[..end].join("\n")
+}
+
+fn is_normal(s: &str) -> bool {
+    !s.starts_with('\u{feff}') &&
+    !s.ends_with(char::is_whitespace) &&
+    s.split('\n').all(|line| !line.ends_with(char::is_whitespace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // The days keeping slices of their inputs answer the same when they own them
+    #[test]
+    fn owned_days() {
+        let year = y2020::year();
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/input");
+        for d in [2, 4, 7] {
+            let input = normalise(fs::read_to_string(format!("{}/{:02}.txt", dir, d)).unwrap());
+            let parser = year.day_parsers[d - 1];
+            let borrowed: Vec<i64> = parser(&input).parts.iter().map(|p| p.solve()).collect();
+            let owned = OwnedDay::parse(parser, input.clone());
+            assert_eq!(owned.parts().iter().map(|p| p.solve()).collect::<Vec<_>>(), borrowed, "d{:02}", d);
+        }
+        // A panicking parser releases the input
+        assert!(std::panic::catch_unwind(|| OwnedDay::parse(year.day_parsers[14], "x".to_string())).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::cell::Cell;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+// Leveled debug logging for the days, filtered by year, day and part:
+//
+//     log::debug!("inserting {} at {:b}", value, addr);
+//
+// The messages go to stderr (or a log file), never to the results on stdout. When a message's level is filtered out
+// its arguments are not even formatted, and when logging is off altogether a message costs a single atomic load.
+// Built without the "log" feature, the messages are compiled out and cost nothing.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+const LEVELS: [(Level, &str); 6] = [(Level::Off, "off"), (Level::Error, "error"), (Level::Warn, "warn"), (Level::Info, "info"),
+                                    (Level::Debug, "debug"), (Level::Trace, "trace")];
+
+impl Level {
+    pub fn parse(s: &str) -> Option<Level> {
+        LEVELS.iter().find(|(_, name)| *name == s).map(|(level, _)| *level)
+    }
+
+    pub fn name(&self) -> &'static str {
+        LEVELS.iter().find(|(level, _)| level == self).unwrap().1
+    }
+}
+
+// The level of the messages logged by a year, day or part, all of them if not given: [SELECTOR=]LEVEL, where the
+// selector is a year, a day and a part, in this order, separated by "/", like 2020/d14/p2, d14 or 2020
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter {
+    pub year: Option<u16>,
+    pub day: Option<usize>,
+    pub part: Option<usize>,
+    pub level: Level,
+}
+
+impl Filter {
+    pub fn parse(s: &str) -> Option<Filter> {
+        let (selector, level) = match s.split_once('=') {
+            Some((selector, level)) => (Some(selector), level),
+            None => (None, s)
+        };
+        let mut filter = Filter { year: None, day: None, part: None, level: Level::parse(level)? };
+        let mut fields = selector.map_or(Vec::new(), |s| s.split('/').collect()).into_iter().peekable();
+        // AoC started in 2015, an earlier "year" is a day without its "d"
+        if let Some(year) = fields.next_if(|f| f.starts_with(|c: char| c.is_ascii_digit())) {
+            filter.year = Some(year.parse().ok().filter(|&year| year >= 2015)?);
+        }
+        if let Some(day) = fields.next_if(|f| f.starts_with('d')) {
+            filter.day = Some(day[1..].parse().ok()?);
+        }
+        if let Some(part) = fields.next_if(|f| f.starts_with('p')) {
+            filter.part = Some(part[1..].parse().ok()?);
+        }
+        if fields.next().is_some() || selector.is_some_and(|_| filter.year.is_none() && filter.day.is_none() && filter.part.is_none()) {
+            return None
+        }
+        Some(filter)
+    }
+
+    fn matches(&self, year: u16, day: usize, part: usize) -> bool {
+        self.year.is_none_or(|y| y == year) && self.day.is_none_or(|d| d == day) && self.part.is_none_or(|p| p == part)
+    }
+
+    // The filters of single parts override those of whole days, which override those of all the days
+    fn specificity(&self) -> usize {
+        self.year.is_some() as usize + 2 * self.day.is_some() as usize + 4 * self.part.is_some() as usize
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut fields = Vec::new();
+        if let Some(year) = self.year { fields.push(year.to_string()) }
+        if let Some(day) = self.day { fields.push(format!("d{}", day)) }
+        if let Some(part) = self.part { fields.push(format!("p{}", part)) }
+        if !fields.is_empty() { write!(f, "{}=", fields.join("/"))? }
+        write!(f, "{}", self.level.name())
+    }
+}
+
+struct Logger {
+    filters: Vec<Filter>,
+    out: Box<dyn Write + Send>,
+}
+
+// The most detailed level any filter enables, checked before anything else
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+
+thread_local! {
+    // The day running on this thread, and the level of the messages it may log
+    static CONTEXT: Cell<(u16, usize, usize, Level)> = const { Cell::new((0, 0, 0, Level::Off)) };
+}
+
+// Called by the runner at the start to log the messages allowed by the filters into `out`
+pub fn init(filters: Vec<Filter>, out: Box<dyn Write + Send>) {
+    let max = filters.iter().map(|f| f.level).max().unwrap_or(Level::Off);
+    *LOGGER.lock().unwrap() = Some(Logger { filters, out });
+    MAX_LEVEL.store(max as u8, Ordering::Relaxed);
+}
+
+pub fn init_stderr(filters: Vec<Filter>) {
+    init(filters, Box::new(io::stderr()))
+}
+
+// Called by the runner before parsing a day (part 0) or solving a part of it. Outside of these, nothing is logged.
+pub fn enter(year: u16, day: usize, part: usize) {
+    if !cfg!(feature = "log") || MAX_LEVEL.load(Ordering::Relaxed) == Level::Off as u8 { return }
+    let level = LOGGER.lock().unwrap().as_ref().map_or(Level::Off, |logger| {
+        let mut matching: Vec<&Filter> = logger.filters.iter().filter(|f| f.matches(year, day, part)).collect();
+        // The last of the most specific filters wins
+        matching.sort_by_key(|f| f.specificity());
+        matching.last().map_or(Level::Off, |f| f.level)
+    });
+    CONTEXT.with(|c| c.set((year, day, part, level)));
+}
+
+pub fn leave() {
+    CONTEXT.with(|c| c.set((0, 0, 0, Level::Off)));
+}
+
+#[inline]
+pub fn enabled(level: Level) -> bool {
+    MAX_LEVEL.load(Ordering::Relaxed) >= level as u8 && CONTEXT.with(|c| c.get().3 >= level)
+}
+
+// Used by the macros once the level is known to be enabled
+pub fn write(level: Level, args: fmt::Arguments) {
+    let (year, day, part, _) = CONTEXT.with(|c| c.get());
+    let stage = if part == 0 { "parse".to_string() } else { format!("p{:02}", part) };
+    if let Some(logger) = LOGGER.lock().unwrap().as_mut() {
+        let _ = writeln!(logger.out, "y{:04} d{:02} {:5} {:5} {}", year, day, stage, level.name().to_uppercase(), args);
+    }
+}
+
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        if cfg!(feature = "log") && $crate::log::enabled($level) {
+            $crate::log::write($level, format_args!($($arg)+))
+        }
+    }
+}
+
+// Not every level is used by the days yet
+#[allow(unused_macros)]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Error, $($arg)+) }
+}
+
+#[allow(unused_macros)]
+macro_rules! warning {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Warn, $($arg)+) }
+}
+
+#[allow(unused_macros)]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Info, $($arg)+) }
+}
+
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Debug, $($arg)+) }
+}
+
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Trace, $($arg)+) }
+}
+
+#[allow(unused_imports)]
+pub(crate) use {debug, error, info, log, trace, warning};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    // Collects the log in memory
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn parse_filters() {
+        for s in ["debug", "d14=trace", "2020/d14=info", "2020/d14/p2=off", "d14/p2=debug", "2020=warn", "p1=error"] {
+            assert_eq!(Filter::parse(s).unwrap().to_string(), s);
+        }
+        assert_eq!(Filter::parse("2020/d14/p2=warn"), Some(Filter { year: Some(2020), day: Some(14), part: Some(2), level: Level::Warn }));
+        for s in ["verbose", "d14=", "x=debug", "14=debug", "d14/2020=debug", "2020/d14/p2/p1=debug", "=debug", "d=debug"] {
+            assert_eq!(Filter::parse(s), None, "{}", s);
+        }
+    }
+
+    // The only test touching the global logger, the tests run in parallel
+    #[test]
+    fn filtered_messages() {
+        let buffer = Buffer::default();
+        let filters = ["info", "d14=debug", "2020/d14/p2=trace", "d11=off"].iter().map(|s| Filter::parse(s).unwrap()).collect();
+        init(filters, Box::new(buffer.clone()));
+        let mut formatted = 0;
+        let mut count = || { formatted += 1; formatted };
+        trace!("outside of the days {}", count());
+        for (day, part) in [(1, 0), (14, 1), (14, 2), (11, 2)] {
+            enter(2020, day, part);
+            info!("info {}", count());
+            debug!("debug {}", count());
+            trace!("trace {}", count());
+            leave();
+        }
+        init(Vec::new(), Box::new(io::sink()));
+        let log = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        if !cfg!(feature = "log") {
+            assert_eq!((log.as_str(), formatted), ("", 0));
+            return
+        }
+        assert_eq!(log, "y2020 d01 parse INFO  info 1\n\
+                         y2020 d14 p01   INFO  info 2\n\
+                         y2020 d14 p01   DEBUG debug 3\n\
+                         y2020 d14 p02   INFO  info 4\n\
+                         y2020 d14 p02   DEBUG debug 5\n\
+                         y2020 d14 p02   TRACE trace 6\n");
+        // The arguments of the filtered out messages are not evaluated
+        assert_eq!(formatted, 6);
+    }
+}
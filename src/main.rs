@@ -1,58 +1,432 @@
 use std::fs;
+use std::io::{self, IsTerminal};
 use std::path::Path;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use adventofcode2020::{log, progress, trace, vis, years, Details, OwnedDay, Year};
+use colour::c;
+use config::Options;
+use spoiler::Solution;
 
-pub struct Year {
-    year: u16,
-    day_parsers: Vec<fn(String) -> Day>,
+struct PartResult {
+    part: usize,
+    actual: i64,
+    expected: Solution,
+    nanos: u128,
+    // The spans the part opened, if it was traced
+    spans: Vec<trace::Record>,
 }
 
-pub struct Day {
-    parts: Vec<Box<dyn Part>>,
+impl PartResult {
+    fn ok(&self) -> bool {
+        self.expected.matches(self.actual)
+    }
+}
+
+struct DayResult {
+    day: usize,
+    // None if the input is missing
+    parse_nanos: Option<u128>,
+    parse_spans: Vec<trace::Record>,
+    parts: Vec<PartResult>,
+    // The answers (and timings) come from the cache of an earlier run
+    cached: bool,
 }
 
-pub trait Part {
-    fn solve(&self) -> i64;
+impl DayResult {
+    // Total time spent on parsing and solving the day
+    fn nanos(&self) -> u128 {
+        self.parse_nanos.unwrap_or(0) + self.parts.iter().map(|p| p.nanos).sum::<u128>()
+    }
 }
 
-fn solutions(from: &Path) -> Vec<Vec<i64>> {
-    let mut res: Vec<Vec<i64>> = Vec::new();
+fn solutions(from: &Path) -> Vec<Vec<Solution>> {
+    let mut res: Vec<Vec<Solution>> = Vec::new();
     if from.is_file() {
         for line in fs::read_to_string(from).unwrap().lines() {
-            res.push(line.split_whitespace().map(|x| x.parse().unwrap()).collect())
+            res.push(line.split_whitespace().map(|x| Solution::parse(x).unwrap()).collect())
         }
     }
     res
 }
 
-mod y2020;
+mod baseline;
+mod cache;
+mod colour;
+mod config;
+mod crypto;
+mod dashboard;
+mod external;
+mod fetch;
+mod hash;
+mod http;
+mod input;
+mod journal;
+mod json;
+mod leaderboard;
+mod profile;
+mod repl;
+mod report;
+mod serve;
+mod shrink;
+mod spoiler;
+mod submit;
+mod verify;
+mod watch;
 
-fn run_year(year: &Year, dir: &str) {
-    let dir = Path::new(dir);
+// The registered years selected by the options
+fn selected_years(opts: &Options) -> Vec<Year> {
+    let years: Vec<Year> = years().into_iter().filter(|y| opts.years.is_empty() || opts.years.contains(&y.year)).collect();
+    if years.is_empty() {
+        usage_error("none of the selected years are implemented")
+    }
+    years
+}
+
+fn run_year(year: &Year, dir: &Path, opts: &Options) -> Vec<DayResult> {
+    let mut results = Vec::new();
     let sols = solutions(&dir.join("solutions.txt"));
+    let journal = journal::Journal::load(&dir.join(journal::JOURNAL_FILE));
+    let mut cache = if opts.use_cache() { Some(cache::Cache::open(&opts.cache_path)) } else { None };
     for (d, (day_parser, day_sols)) in year.day_parsers.iter().zip(sols.iter()).enumerate() {
         let d = d + 1;
-        let input = dir.join(format!("{:02}.txt", d));
-        if input.is_file() {
-            let input_str = fs::read_to_string(input).unwrap();
+        if !opts.days.is_empty() && !opts.days.contains(&d) { continue }
+        let path = dir.join(format!("{:02}.txt", d));
+        if input::exists(&path) {
+            let input_str = match input::read(&path, &opts.key_file) {
+                Ok(input_str) => input_str,
+                Err(e) => {
+                    println!("{}[ERR] y{:04} d{:02}     cannot read {}: {}{}", c(colour::RED), year.year, d, path.display(), e, c(colour::RESET));
+                    results.push(DayResult { day: d, parse_nanos: None, parse_spans: Vec::new(), parts: Vec::new(), cached: false });
+                    continue
+                }
+            };
+            let input_hash = hash::fnv1a(input_str.as_bytes());
+            let cached = cache.as_ref().filter(|_| opts.use_cache_for(year.year, d)).and_then(|cache| cached(cache, year.year, d, input_hash, day_sols));
+            if let Some(day_result) = cached {
+                print_cached(year.year, &day_result, &journal);
+                results.push(day_result);
+                continue
+            }
+            if opts.tracing() { trace::start() }
+            log::enter(year.year, d, 0);
             let t = Instant::now();
-            let day = day_parser(input_str);
+            let day = day_parser(&input_str);
             let t = t.elapsed().as_nanos();
-            println!("\x1b[33m      y{:04} d{:02} {:24} {:14.3} μs\x1b[0m", year.year, d, "parsing", t as f32 / 1000.0);
+            log::leave();
+            let parse_spans = trace::finish();
+            println!("{}      y{:04} d{:02} {:24} {:14.3} μs{}", c(colour::YELLOW), year.year, d, "parsing", t as f32 / 1000.0, c(colour::RESET));
+            if opts.trace {
+                print_spans(&parse_spans, t);
+            }
+            let mut day_result = DayResult { day: d, parse_nanos: Some(t), parse_spans, parts: Vec::new(), cached: false };
             for (p, (part, expected)) in day.parts.iter().zip(day_sols.iter()).enumerate() {
                 let p = p + 1;
+                progress::start(format!("      y{:04} d{:02} p{:02}", year.year, d, p));
+                if opts.tracing() { trace::start() }
+                log::enter(year.year, d, p);
                 let t = Instant::now();
                 let actual = part.solve();
                 let t = t.elapsed().as_nanos();
-                let result = if *expected == actual { "[\x1b[32mOK\x1b[0m] " } else { "\x1b[31m[ERR]" };
-                println!("{} y{:04} d{:02} p{:02} {:20} {:14.3} μs\x1b[0m", result, year.year, d, p, actual, t as f32 / 1000.0)
+                log::leave();
+                let spans = trace::finish();
+                progress::finish();
+                let part_result = PartResult { part: p, actual, expected: expected.clone(), nanos: t, spans };
+                print_part(Some(part_result.ok()), year.year, d, p, actual, &format!("{:14.3} μs", t as f32 / 1000.0));
+                if let Some(warning) = journal.check(d, p, actual) {
+                    println!("{}{:20}warning: {}{}", c(colour::RED), "", warning, c(colour::RESET));
+                }
+                if opts.trace {
+                    print_spans(&part_result.spans, t);
+                }
+                if opts.explain {
+                    print_details(&part.explain());
+                }
+                if let Some(target) = &opts.visualize {
+                    part.visualize(vis::visualizer(target, year.year, d, p).as_mut());
+                }
+                day_result.parts.push(part_result);
+            }
+            if let Some(cmd) = opts.external(year.year, d) {
+                cross_check(year.year, d, cmd, &input_str, &day_result, opts);
+            }
+            if let Some(cache) = &mut cache {
+                cache.put(year.year, d, 0, input_hash, 0, t);
+                for p in &day_result.parts {
+                    cache.put(year.year, d, p.part, input_hash, p.actual, p.nanos);
+                }
             }
+            results.push(day_result);
         } else {
-            println!("{:?} not found", &input)
+            println!("{:?} not found", &path);
+            results.push(DayResult { day: d, parse_nanos: None, parse_spans: Vec::new(), parts: Vec::new(), cached: false });
         }
     }
+    if let Some(cache) = cache {
+        if let Err(e) = cache.save() {
+            eprintln!("cannot save the cache to {}: {}", opts.cache_path.display(), e);
+        }
+    }
+    results
+}
+
+// The result of a day from the cache, if the parsing and all the parts with a known solution are in it
+// (part 0 holds the time of parsing)
+fn cached(cache: &cache::Cache, year: u16, d: usize, input: u64, day_sols: &[Solution]) -> Option<DayResult> {
+    let (_, parse_nanos) = cache.get(year, d, 0, input)?;
+    let mut parts = Vec::new();
+    for (p, expected) in day_sols.iter().enumerate() {
+        let (actual, nanos) = cache.get(year, d, p + 1, input)?;
+        parts.push(PartResult { part: p + 1, actual, expected: expected.clone(), nanos, spans: Vec::new() });
+    }
+    Some(DayResult { day: d, parse_nanos: Some(parse_nanos), parse_spans: Vec::new(), parts, cached: true })
 }
 
+fn print_cached(year: u16, day_result: &DayResult, journal: &journal::Journal) {
+    let d = day_result.day;
+    let marker = format!("{}(cached){}", c(colour::DIM), c(colour::RESET));
+    println!("{}      y{:04} d{:02} {:24} {:14.3} μs{} {}", c(colour::YELLOW), year, d, "parsing", day_result.parse_nanos.unwrap_or(0) as f32 / 1000.0, c(colour::RESET), marker);
+    for part_result in &day_result.parts {
+        let time = format!("{:14.3} μs{} {}", part_result.nanos as f32 / 1000.0, c(colour::RESET), marker);
+        print_part(Some(part_result.ok()), year, d, part_result.part, part_result.actual, &time);
+        if let Some(warning) = journal.check(d, part_result.part, part_result.actual) {
+            println!("{}{:20}warning: {}{}", c(colour::RED), "", warning, c(colour::RESET));
+        }
+    }
+}
+
+// The line of an answer of a part: whether it is right (when there is an expected answer), then what follows the
+// answer, like the time it took
+fn print_part(ok: Option<bool>, year: u16, d: usize, p: usize, answer: i64, rest: &str) {
+    let result = match ok {
+        Some(true) => format!("[{}OK{}] ", c(colour::GREEN), c(colour::RESET)),
+        Some(false) => format!("{}[ERR]", c(colour::RED)),
+        None => "     ".to_string()
+    };
+    println!("{} y{:04} d{:02} p{:02} {:>20} {}{}", result, year, d, p, spoiler::answer(answer), rest, c(colour::RESET));
+}
+
+// The details of an answer from Part::explain, unless they could spoil it
+fn print_details(details: &Details) {
+    if spoiler::hidden() {
+        if !details.is_empty() { println!("{:20}(details hidden)", "") }
+        return
+    }
+    for (key, value) in details {
+        println!("{:20}{}: {}", "", key, value)
+    }
+}
+
+// The spans of parsing or a part taking `nanos`, nested spans indented under their parents
+fn print_spans(spans: &[trace::Record], nanos: u128) {
+    for span in spans {
+        let name = format!("{:indent$}{}", "", span.path.last().unwrap(), indent = 2 * (span.path.len() - 1));
+        println!("{:20}{:28} {:14.3} μs {:5.1}% {:>8}", "", name, span.nanos as f32 / 1000.0,
+                 100.0 * span.nanos as f64 / nanos.max(1) as f64, format!("×{}", span.count));
+    }
+}
+
+// All the spans of a run in the folded stack format, under the year, day and part (or "parse")
+fn write_folded(path: &Path, years: &[(u16, Vec<DayResult>)]) -> io::Result<()> {
+    let mut lines = Vec::new();
+    for (year, results) in years {
+        for r in results.iter().filter(|r| !r.cached) {
+            if let Some(nanos) = r.parse_nanos {
+                lines.extend(trace::folded(&format!("y{:04};d{:02};parse", year, r.day), Duration::from_nanos(nanos as u64), &r.parse_spans));
+            }
+            for p in &r.parts {
+                lines.extend(trace::folded(&format!("y{:04};d{:02};p{:02}", year, r.day, p.part), Duration::from_nanos(p.nanos as u64), &p.spans));
+            }
+        }
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, lines.join("\n") + "\n")
+}
+
+fn usage_error(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1)
+}
+
+// Run an external solver on the same input and compare its answers with ours and the expected ones
+fn cross_check(year: u16, d: usize, cmd: &str, input: &str, day_result: &DayResult, opts: &Options) {
+    let outcome = external::run(cmd, input, opts.external_timeout);
+    println!("{}      y{:04} d{:02} {:24} {:14.3} μs{}", c(colour::YELLOW), year, d, "external", outcome.nanos as f32 / 1000.0, c(colour::RESET));
+    match outcome.answers {
+        Err(e) => println!("{}[ERR] y{:04} d{:02}     {}: {}{}", c(colour::RED), year, d, cmd, e, c(colour::RESET)),
+        Ok(answers) => for (i, part) in day_result.parts.iter().enumerate() {
+            let p = i + 1;
+            match answers.get(i) {
+                None => println!("{}[ERR] y{:04} d{:02} p{:02} {:>20} {}", c(colour::RED), year, d, p, "missing", c(colour::RESET)),
+                Some(&answer) => {
+                    let agrees = if answer == part.actual { "same as ours".to_string() } else { format!("ours is {}", spoiler::answer(part.actual)) };
+                    print_part(Some(part.expected.matches(answer)), year, d, p, answer, &format!("{:>17} {}", "external", agrees));
+                }
+            }
+        }
+    }
+}
+
+// List the journal of the selected years, or add a new entry to it
+fn journal_command(opts: &Options, args: &[String]) {
+    let years = selected_years(opts);
+    match args {
+        [] => for year in &years {
+            let dir = opts.input_dir(year.year);
+            for e in journal::Journal::load(&dir.join(journal::JOURNAL_FILE)).entries {
+                println!("y{:04} d{:02} p{:02} {:>20} {}", year.year, e.day, e.part, spoiler::answer(e.guess), e.verdict.name());
+            }
+        },
+        [cmd, day, part, guess, verdict] if cmd == "add" => {
+            let entry = match (day.parse(), part.parse(), guess.parse(), journal::Verdict::parse(verdict)) {
+                (Ok(day), Ok(part), Ok(guess), Some(verdict)) => journal::Entry { day, part, guess, verdict },
+                _ => usage_error("usage: journal add DAY PART GUESS correct|wrong|high|low")
+            };
+            if years.len() > 1 { usage_error("select a single year with --year") }
+            let path = opts.input_dir(years[0].year).join(journal::JOURNAL_FILE);
+            if let Err(e) = journal::Journal::append(&path, &entry) {
+                usage_error(&format!("cannot write {}: {}", path.display(), e))
+            }
+        },
+        _ => usage_error("usage: journal [add DAY PART GUESS correct|wrong|high|low]")
+    }
+}
+
+// Replace the plain answers of the selected years' solutions.txt with salted hashes
+fn hash_solutions(opts: &Options) {
+    for year in selected_years(opts) {
+        let path = opts.input_dir(year.year).join("solutions.txt");
+        let lines: Vec<String> = solutions(&path).iter()
+            .map(|sols| sols.iter().map(|s| s.hashed().to_string()).collect::<Vec<String>>().join(" "))
+            .collect();
+        if lines.is_empty() { continue }
+        if let Err(e) = fs::write(&path, lines.join("\n") + "\n") {
+            usage_error(&format!("cannot write {}: {}", path.display(), e))
+        }
+        println!("y{:04} {} hashed", year.year, path.display());
+    }
+}
+
+// Write a new random key to the key file, unless it exists already
+fn keygen(opts: &Options) {
+    if opts.key_file.exists() {
+        usage_error(&format!("{} exists already", opts.key_file.display()))
+    }
+    let written = crypto::generate_key().and_then(|key| {
+        if let Some(dir) = opts.key_file.parent() { fs::create_dir_all(dir)? }
+        fs::write(&opts.key_file, key + "\n")
+    });
+    match written {
+        Ok(()) => println!("new key written to {}", opts.key_file.display()),
+        Err(e) => usage_error(&format!("cannot write {}: {}", opts.key_file.display(), e))
+    }
+}
+
+// Encrypt the plain inputs of the selected years and days, or decrypt the encrypted ones missing a plain copy
+fn crypt_inputs(opts: &Options, decrypt: bool) {
+    let key = crypto::load_key(&opts.key_file).unwrap_or_else(|e| usage_error(&e));
+    for year in selected_years(opts) {
+        let dir = opts.input_dir(year.year);
+        // Inputs of days that are not implemented yet are encrypted too
+        for d in (1..=25).filter(|d| opts.days.is_empty() || opts.days.contains(d)) {
+            let plain = dir.join(format!("{:02}.txt", d));
+            let encrypted = input::encrypted(&plain);
+            let (from, to) = if decrypt { (&encrypted, &plain) } else { (&plain, &encrypted) };
+            if !from.is_file() || (decrypt && to.exists()) { continue }
+            let result = fs::read(from).and_then(|data| if decrypt {
+                crypto::decrypt(&key, &data).map_err(io::Error::other)
+            } else {
+                crypto::encrypt(&key, &data)
+            }).and_then(|data| fs::write(to, data));
+            match result {
+                Ok(()) => println!("y{:04} d{:02} {} -> {}", year.year, d, from.display(), to.display()),
+                Err(e) => println!("{}[ERR] y{:04} d{:02} {}: {}{}", c(colour::RED), year.year, d, from.display(), e, c(colour::RESET))
+            }
+        }
+    }
+}
+
+fn run(opts: &Options) {
+    let years = selected_years(opts);
+    if opts.watch {
+        watch::watch(&years, opts)
+    }
+
+    let mut all_results = Vec::new();
+    for year in &years {
+        let results = run_year(year, &opts.input_dir(year.year), opts);
+        if opts.dashboard.resolve(std::io::stdout().is_terminal()) {
+            dashboard::print(year, &results);
+        }
+        if opts.save_baseline {
+            if let Err(e) = baseline::save(&opts.baseline, year.year, &results) {
+                eprintln!("cannot write {}: {}", opts.baseline.display(), e);
+            }
+        }
+        all_results.push((year.year, results));
+    }
+
+    if let Some(folded) = &opts.folded {
+        if let Err(e) = write_folded(folded, &all_results) {
+            eprintln!("cannot write {}: {}", folded.display(), e);
+        }
+    }
+
+    if let Some(html) = &opts.html {
+        let baseline = baseline::load(&opts.baseline);
+        let years: Vec<(u16, &[DayResult])> = all_results.iter().map(|(y, r)| (*y, r.as_slice())).collect();
+        if let Err(e) = report::write_html(html, &years, &baseline, opts.regression_threshold) {
+            eprintln!("cannot write {}: {}", html.display(), e);
+        }
+    }
+}
+
+// The commands running the days, the others never log (and don't create the log file)
+const LOGGING_COMMANDS: [&str; 6] = ["run", "verify", "repl", "submit", "serve", "solve"];
+
 fn main() {
-    run_year(&y2020::year(), "input");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (command, args) = match args.first() {
+        Some(cmd) if !cmd.starts_with("--") => (cmd.as_str(), &args[1..]),
+        _ => ("run", &args[..])
+    };
+
+    let (opts, positional) = config::options(args);
+    colour::set_enabled(opts.colour.resolve(std::io::stdout().is_terminal()));
+    spoiler::set_display(opts.spoilers, &opts.spoiler_salt);
+    if !opts.log.is_empty() && LOGGING_COMMANDS.contains(&command) {
+        // The solve commands run by the server append to the log file the server created
+        let file = |path| if command == "solve" { fs::OpenOptions::new().append(true).create(true).open(path) } else { fs::File::create(path) };
+        match &opts.log_file {
+            Some(path) => match file(path) {
+                Ok(file) => log::init(opts.log.clone(), Box::new(io::LineWriter::new(file))),
+                Err(e) => usage_error(&format!("cannot write {}: {}", path.display(), e))
+            },
+            None => log::init_stderr(opts.log.clone())
+        }
+    }
+    if !["journal", "submit", "solve", "shrink", "leaderboard"].contains(&command) && !positional.is_empty() {
+        usage_error(&format!("unexpected arguments: {}", positional.join(" ")))
+    }
+    match command {
+        "run" => run(&opts),
+        "verify" => std::process::exit(verify::verify(&opts)),
+        "journal" => journal_command(&opts, &positional),
+        "repl" => repl::repl(&selected_years(&opts), &opts),
+        "config" => print!("{}", opts.show()),
+        "hash-solutions" => hash_solutions(&opts),
+        "c-header" => print!("{}", adventofcode2020::ffi::header()),
+        "fetch" => fetch::fetch(&opts),
+        "submit" => submit::submit(&opts, &positional),
+        "profile" => profile::profile(&opts),
+        "leaderboard" => leaderboard::leaderboard(&positional),
+        "shrink" => shrink::shrink(&opts, &positional),
+        "serve" => serve::serve(&selected_years(&opts), &opts),
+        "solve" => serve::solve_command(&selected_years(&opts), &positional),
+        "keygen" => keygen(&opts),
+        "encrypt" => crypt_inputs(&opts, false),
+        "decrypt" => crypt_inputs(&opts, true),
+        _ => usage_error(&format!("unknown command: {}", command))
+    }
 }
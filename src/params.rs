@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+use std::ptr;
+
+// A number fixed by the puzzle text, like the 25 number preamble of d09, which a day reads when solving instead of
+// hardcoding it. Declare it as a static next to the day and register it with the year:
+//
+//     pub static PREAMBLE: Param = Param { name: "preamble", default: 25, min: 2 };
+//
+// The runner always uses the defaults, the REPL and the profiler may change the values on their thread.
+pub struct Param {
+    pub name: &'static str,
+    pub default: usize,
+    // The smallest value the day (and its generator) can work with
+    pub min: usize,
+}
+
+thread_local! {
+    static VALUES: RefCell<Vec<(&'static Param, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Param {
+    pub fn get(&'static self) -> usize {
+        VALUES.with(|v| v.borrow().iter().find(|(p, _)| ptr::eq(*p, self)).map_or(self.default, |(_, value)| *value))
+    }
+
+    pub fn set(&'static self, value: usize) -> Result<(), String> {
+        if value < self.min {
+            return Err(format!("{} must be at least {}", self.name, self.min))
+        }
+        self.reset();
+        VALUES.with(|v| v.borrow_mut().push((self, value)));
+        Ok(())
+    }
+
+    // Back to the default
+    pub fn reset(&'static self) {
+        VALUES.with(|v| v.borrow_mut().retain(|(p, _)| !ptr::eq(*p, self)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    static WINDOW: Param = Param { name: "window", default: 25, min: 2 };
+    static TURNS: Param = Param { name: "turns", default: 2020, min: 1 };
+
+    #[test]
+    fn values_per_thread() {
+        assert_eq!(WINDOW.get(), 25);
+        WINDOW.set(5).unwrap();
+        assert!(WINDOW.set(1).is_err());
+        assert_eq!((WINDOW.get(), TURNS.get()), (5, 2020));
+        assert_eq!(thread::spawn(|| WINDOW.get()).join().unwrap(), 25);
+        WINDOW.reset();
+        assert_eq!(WINDOW.get(), 25);
+    }
+}
@@ -0,0 +1,260 @@
+use std::hint::black_box;
+use std::panic;
+use std::time::{Duration, Instant};
+use adventofcode2020::rng::Rng;
+use adventofcode2020::params::Param;
+use adventofcode2020::DayParser;
+use crate::{selected_years, Options};
+
+// The sizes of the generated inputs double from the first one, until a size takes longer than the budget to solve
+const FIRST_SIZE: usize = 16;
+const MAX_SIZE: usize = 1 << 20;
+const BUDGET: Duration = Duration::from_millis(500);
+// The fast sizes are repeated for at least this long (but not too many times), and the fastest runs count
+const MIN_TIME: Duration = Duration::from_millis(20);
+const MAX_RUNS: usize = 100;
+// Timings shorter than this are mostly noise, they are not fitted if there are enough longer ones
+const MIN_NANOS: f64 = 1000.0;
+
+// A complexity class, as its term of n
+type Class = (&'static str, fn(f64) -> f64);
+
+const CLASSES: [Class; 6] = [
+    ("1", |_| 1.0),
+    ("log n", |n| n.ln()),
+    ("n", |n| n),
+    ("n log n", |n| n * n.ln()),
+    ("n²", |n| n * n),
+    ("n³", |n| n * n * n),
+];
+
+// The timing of parsing or a part (in ns) on an input of size n, generated with the day's puzzle parameter set to k
+// (1 for the days without a parameter)
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub n: usize,
+    pub k: usize,
+    pub nanos: f64,
+}
+
+// The classes fitted to the timings and the exponents of the sizes, k is only fitted if it was varied
+#[derive(Debug)]
+pub struct Fit {
+    pub n: (&'static str, f64),
+    pub k: Option<(&'static str, f64)>,
+}
+
+impl Fit {
+    // Like O(n·k²), the terms that don't grow are left out
+    pub fn class(&self) -> String {
+        let mut terms: Vec<String> = Vec::new();
+        if self.n.0 != "1" { terms.push(self.n.0.to_string()) }
+        if let Some((k, _)) = self.k.filter(|(k, _)| *k != "1") { terms.push(k.replace('n', "k")) }
+        if terms.is_empty() { terms.push("1".to_string()) }
+        format!("O({})", terms.join("·"))
+    }
+
+    pub fn exponents(&self) -> String {
+        match self.k {
+            Some((_, k)) => format!("~n^{:.2} k^{:.2}", self.n.1, k),
+            None => format!("~n^{:.2}", self.n.1)
+        }
+    }
+}
+
+// The fastest parse and solve times of the parts (in ns) on an input
+fn measure(parser: DayParser, input: &str) -> Vec<f64> {
+    let mut best: Vec<f64> = Vec::new();
+    let start = Instant::now();
+    for _ in 0..MAX_RUNS {
+        let t = Instant::now();
+        let day = parser(input);
+        let mut nanos = vec![t.elapsed().as_nanos() as f64];
+        for part in &day.parts {
+            let t = Instant::now();
+            black_box(part.solve());
+            nanos.push(t.elapsed().as_nanos() as f64);
+        }
+        best = if best.is_empty() { nanos } else { best.iter().zip(nanos).map(|(&b, n)| b.min(n)).collect() };
+        if start.elapsed() >= MIN_TIME { break }
+    }
+    best
+}
+
+fn median(mut xs: Vec<f64>) -> f64 {
+    xs.sort_by(f64::total_cmp);
+    xs[xs.len() / 2]
+}
+
+// How the time grows with one of the sizes: the median exponent of the size between consecutive samples where only
+// that size differs, None if it never does. The median ignores the jumps where the data of a part outgrows a cache.
+fn exponent(samples: &[Sample], size: fn(&Sample) -> usize, other: fn(&Sample) -> usize) -> Option<f64> {
+    let mut samples = samples.to_vec();
+    samples.sort_by_key(|s| (other(s), size(s)));
+    let exponents: Vec<f64> = samples.windows(2)
+        .filter(|w| other(&w[0]) == other(&w[1]) && size(&w[0]) != size(&w[1]))
+        .map(|w| (w[1].nanos / w[0].nanos).ln() / (size(&w[1]) as f64 / size(&w[0]) as f64).ln())
+        .collect();
+    if exponents.is_empty() { None } else { Some(median(exponents)) }
+}
+
+// The class growing the most like the timings with one of the sizes, and the exponent of the timings
+fn fit_size(samples: &[Sample], size: fn(&Sample) -> usize, other: fn(&Sample) -> usize) -> Option<(&'static str, f64)> {
+    let e = exponent(samples, size, other)?;
+    let distance = |&(_, f): &Class| {
+        let class: Vec<Sample> = samples.iter().map(|s| Sample { nanos: f(size(s) as f64), ..*s }).collect();
+        (exponent(&class, size, other).unwrap() - e).abs()
+    };
+    let class = CLASSES.iter().min_by(|a, b| distance(a).total_cmp(&distance(b))).unwrap();
+    Some((class.0, e))
+}
+
+// The classes of n (and k) growing the most like the timings. Timings of 0 ns don't grow with anything, they are
+// left out, and so are the ones shorter than MIN_NANOS if there are enough longer ones.
+pub fn fit(samples: &[Sample]) -> Option<Fit> {
+    let samples: Vec<Sample> = samples.iter().copied().filter(|s| s.nanos > 0.0).collect();
+    let long: Vec<Sample> = samples.iter().copied().filter(|s| s.nanos >= MIN_NANOS).collect();
+    let samples = if long.len() >= 3 { long } else { samples };
+    Some(Fit { n: fit_size(&samples, |s| s.n, |s| s.k)?, k: fit_size(&samples, |s| s.k, |s| s.n) })
+}
+
+// The values of the first puzzle parameter of a day to profile with: from the default up to 8 times the default, as
+// the growth only shows on the large values
+fn param_values(param: Option<&'static Param>) -> Vec<usize> {
+    match param {
+        Some(param) => [1, 2, 4, 8].iter().map(|m| param.default * m).collect(),
+        None => vec![1]
+    }
+}
+
+// Time the selected days on generated inputs of growing sizes, and estimate how parsing and the parts scale. The first
+// puzzle parameter of a day, if it has any, is varied too.
+pub fn profile(opts: &Options) {
+    for year in selected_years(opts) {
+        for (i, (&parser, &generate)) in year.day_parsers.iter().zip(&year.generators).enumerate() {
+            let d = i + 1;
+            if !opts.days.is_empty() && !opts.days.contains(&d) { continue }
+            let param = year.params(d).first().copied();
+            // Samples of parsing and the parts
+            let mut samples: Vec<Vec<Sample>> = Vec::new();
+            for k in param_values(param) {
+                if let Some(param) = param { param.set(k).unwrap() }
+                let mut rng = Rng::new(year.year as u64 * 100 + d as u64);
+                let mut sizes = 0;
+                let mut n = FIRST_SIZE;
+                while n <= MAX_SIZE {
+                    let input = match generate(n, &mut rng) {
+                        Some(input) => input,
+                        // Too small for the day, or too large
+                        None if sizes == 0 => { n *= 2; continue },
+                        None => break
+                    };
+                    let t = Instant::now();
+                    let nanos = match panic::catch_unwind(|| measure(parser, &input)) {
+                        Ok(nanos) => nanos,
+                        Err(_) => {
+                            println!("y{:04} d{:02} failed on a generated input of size {}", year.year, d, n);
+                            break
+                        }
+                    };
+                    samples.resize(nanos.len(), Vec::new());
+                    for (s, nanos) in samples.iter_mut().zip(nanos) {
+                        s.push(Sample { n, k, nanos });
+                    }
+                    sizes += 1;
+                    if t.elapsed() > BUDGET { break }
+                    n *= 2;
+                }
+                if let Some(param) = param { param.reset() }
+            }
+            let fits: Vec<Option<Fit>> = samples.iter().map(|s| fit(s)).collect();
+            if fits.first().is_none_or(|f| f.is_none()) {
+                println!("y{:04} d{:02} too slow to profile, the smallest input took more than {:?}", year.year, d, BUDGET);
+            }
+            for (p, (s, fit)) in samples.iter().zip(fits).enumerate() {
+                let fit = match fit {
+                    Some(fit) => fit,
+                    None => continue
+                };
+                let stage = if p == 0 { "parse".to_string() } else { format!("p{:02}", p) };
+                let range = |size: fn(&Sample) -> usize| {
+                    let (lo, hi) = (s.iter().map(size).min().unwrap(), s.iter().map(size).max().unwrap());
+                    format!("{:>7} .. {:<7}", lo, hi)
+                };
+                let ks = match param {
+                    Some(param) => format!(" {} = {}", param.name, range(|s| s.k)),
+                    None => String::new()
+                };
+                let (fastest, slowest) = s.iter().map(|s| s.nanos).fold((f64::MAX, 0.0f64), |(lo, hi), t| (lo.min(t), hi.max(t)));
+                println!("y{:04} d{:02} {:5} {:13} {:16} n = {}{} {:14.3} μs .. {:14.3} μs",
+                         year.year, d, stage, fit.class(), fit.exponents(), range(|s| s.n), ks, fastest / 1000.0, slowest / 1000.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adventofcode2020::years;
+
+    #[test]
+    fn fit_classes() {
+        let sizes = [16, 32, 64, 128, 256, 512, 1024, 2048];
+        let noise = |n: usize| 1.0 + (n % 7) as f64 / 50.0;
+        let samples = |f: fn(f64) -> f64| sizes.iter().map(|&n| Sample { n, k: 1, nanos: 50.0 * f(n as f64) * noise(n) }).collect::<Vec<_>>();
+        for &(name, f) in &CLASSES {
+            let fit = fit(&samples(f)).unwrap();
+            assert_eq!(fit.class(), format!("O({})", name));
+            assert!(fit.k.is_none());
+        }
+        assert!((fit(&samples(|n| n * n)).unwrap().n.1 - 2.0).abs() < 0.1);
+        // Timings of 0 ns are left out
+        let mut zeros = samples(|n| n);
+        zeros[0].nanos = 0.0;
+        zeros[3].nanos = 0.0;
+        assert_eq!(fit(&zeros).unwrap().class(), "O(n)");
+        assert!(fit(&samples(|_| 0.0)).is_none());
+    }
+
+    #[test]
+    fn fit_two_sizes() {
+        let samples = |f: fn(f64, f64) -> f64| {
+            let mut samples = Vec::new();
+            for k in [3, 6, 12, 25] {
+                // The larger k, the fewer sizes, like the generated inputs of d09
+                for n in (5..11 + k / 6).map(|e| 1 << e) {
+                    samples.push(Sample { n, k, nanos: f(n as f64, k as f64) * (1.0 + ((n + k) % 7) as f64 / 50.0) });
+                }
+            }
+            fit(&samples).unwrap()
+        };
+        let fit = samples(|n, k| n * k * k);
+        assert_eq!(fit.class(), "O(n·k²)");
+        assert!((fit.k.unwrap().1 - 2.0).abs() < 0.1);
+        assert_eq!(samples(|n, _| n).class(), "O(n)");
+        assert_eq!(samples(|_, k| k).class(), "O(k)");
+        assert_eq!(samples(|n, k| n * n.ln() * k.ln()).class(), "O(n log n·log k)");
+        assert_eq!(samples(|_, _| 1.0).class(), "O(1)");
+    }
+
+    #[test]
+    fn param_sizes() {
+        static WINDOW: Param = Param { name: "window", default: 25, min: 2 };
+        assert_eq!(param_values(Some(&WINDOW)), [25, 50, 100, 200]);
+        assert_eq!(param_values(None), [1]);
+    }
+
+    #[test]
+    fn generated_inputs_solve() {
+        let mut rng = Rng::new(1);
+        for year in years() {
+            for (d, (parser, generate)) in year.day_parsers.iter().zip(&year.generators).enumerate() {
+                let input = generate(40, &mut rng).unwrap_or_else(|| panic!("y{:04} d{:02}", year.year, d + 1));
+                for part in &parser(&input).parts {
+                    part.solve();
+                }
+            }
+        }
+    }
+}
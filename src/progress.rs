@@ -0,0 +1,110 @@
+use std::cell::RefCell;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+// How often the progress line is updated on a terminal and in plain output
+const TTY_INTERVAL: Duration = Duration::from_millis(100);
+const LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+struct Progress {
+    label: String,
+    start: Instant,
+    last: Instant,
+    tty: bool,
+    shown: bool,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Progress>> = const { RefCell::new(None) };
+}
+
+// Called by the runner before solving a part
+pub fn start(label: String) {
+    let now = Instant::now();
+    CURRENT.with(|c| *c.borrow_mut() = Some(Progress {
+        label,
+        start: now,
+        last: now,
+        tty: io::stderr().is_terminal(),
+        shown: false,
+    }));
+}
+
+// Called by the runner when the part is done, clears the live progress line
+pub fn finish() {
+    CURRENT.with(|c| {
+        if let Some(p) = c.borrow_mut().take() {
+            if p.tty && p.shown {
+                eprint!("\r\x1b[K");
+            }
+        }
+    })
+}
+
+fn status(label: &str, step: u64, total: Option<u64>, elapsed: Duration) -> String {
+    let elapsed = elapsed.as_secs_f64();
+    let rate = step as f64 / elapsed;
+    // A total of 0 steps can't be right, show it like an unknown one
+    let status = match total.filter(|&total| total > 0) {
+        Some(total) => format!("{}/{} ({:.1}%)", step, total, 100.0 * step as f64 / total as f64),
+        None => format!("step {}", step)
+    };
+    format!("{} {} {:.1} s, {:.0} steps/s", label, status, elapsed, rate)
+}
+
+// Parts may call this from long running loops to report the current step and optionally the total number of steps.
+// It is cheap, but not free: call it once every few thousand iterations only.
+pub fn report(step: u64, total: Option<u64>) {
+    CURRENT.with(|c| {
+        if let Some(p) = c.borrow_mut().as_mut() {
+            let now = Instant::now();
+            let interval = if p.tty { TTY_INTERVAL } else { LOG_INTERVAL };
+            if now - p.last < interval { return }
+            p.last = now;
+            p.shown = true;
+
+            let line = status(&p.label, step, total, now - p.start);
+            if p.tty {
+                eprint!("\r{}\x1b[K", line);
+            } else {
+                eprintln!("{}", line);
+            }
+            io::stderr().flush().unwrap();
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shown() -> Option<bool> {
+        CURRENT.with(|c| c.borrow().as_ref().map(|p| p.shown))
+    }
+
+    #[test]
+    fn start_report_finish() {
+        // Outside of a part reports are ignored
+        report(1, None);
+        assert_eq!(shown(), None);
+
+        start("y2020 d15 p02".to_string());
+        assert_eq!(shown(), Some(false));
+        // Too early to show anything
+        report(1, Some(10));
+        assert_eq!(shown(), Some(false));
+        CURRENT.with(|c| {
+            let mut c = c.borrow_mut();
+            let p = c.as_mut().unwrap();
+            p.last -= LOG_INTERVAL;
+        });
+        report(2, Some(10));
+        assert_eq!(shown(), Some(true));
+        finish();
+        assert_eq!(shown(), None);
+
+        assert_eq!(status("p02", 250, Some(1000), Duration::from_secs(2)), "p02 250/1000 (25.0%) 2.0 s, 125 steps/s");
+        assert_eq!(status("p02", 250, None, Duration::from_millis(500)), "p02 step 250 0.5 s, 500 steps/s");
+        assert_eq!(status("p02", 250, Some(0), Duration::from_millis(500)), "p02 step 250 0.5 s, 500 steps/s");
+    }
+}
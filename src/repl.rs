@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Instant;
+use adventofcode2020::{log, DayParser, Part};
+use crate::{hash, input, print_details, print_part, solutions, Options, OwnedDay, Year};
+use crate::spoiler::Solution;
+
+const HELP: &str = "\
+commands:
+  years                  list the registered years
+  year YYYY              select a year
+  days                   list the days of the selected year
+  load DAY [FILE]        parse the input of a day (input/DD.txt by default)
+  paste DAY              parse an input typed or pasted in, end it with a line containing a single '.'
+  run [PART]             solve all parts (or just one) of the loaded day
+  time PART [N]          solve a part N times and show timing statistics
+  explain [PART]         show the details of the parts' answers
+  set PARAM VALUE        change a parameter (repeat N, explain on|off, dir PATH),
+                         or a puzzle parameter of the selected year (like preamble N of d09, or default)
+  params                 show the parameters
+  cache                  list the cached parsed inputs
+  help                   show this help
+  quit                   exit";
+
+struct Loaded {
+    year: u16,
+    day: usize,
+    source: String,
+    expected: Vec<Solution>,
+    parsed: Rc<OwnedDay>,
+}
+
+// Parse (part 0) or solve a part of a day with its messages logged. The days panic on bad inputs, which must not end
+// the session: the panic becomes an error, its message is printed by the default hook.
+fn guard<T>(year: u16, day: usize, part: usize, f: impl FnOnce() -> T) -> Result<T, String> {
+    log::enter(year, day, part);
+    let res = panic::catch_unwind(AssertUnwindSafe(f));
+    log::leave();
+    let stage = if part == 0 { "parsing".to_string() } else { format!("p{:02}", part) };
+    res.map_err(|_| format!("y{:04} d{:02} {} panicked", year, day, stage))
+}
+
+impl Loaded {
+    fn with_part<T>(&self, p: usize, f: impl FnOnce(&dyn Part) -> T) -> Result<T, String> {
+        guard(self.year, self.day, p, || f(self.parsed.parts()[p - 1].as_ref()))
+    }
+}
+
+struct Session<'a> {
+    years: &'a [Year],
+    year: usize,
+    // The input directory, {year} is replaced with the selected year
+    input: String,
+    key_file: PathBuf,
+    repeat: usize,
+    explain: bool,
+    // Parsed inputs with their own copy of the input and where it came from, keyed by year, day and the hash of
+    // the input. Reloading a changed file replaces its earlier version.
+    cache: HashMap<(u16, usize, u64), (String, Rc<OwnedDay>)>,
+    loaded: Option<Loaded>,
+}
+
+impl<'a> Session<'a> {
+    fn year(&self) -> &'a Year {
+        &self.years[self.year]
+    }
+
+    fn dir(&self) -> PathBuf {
+        PathBuf::from(self.input.replace("{year}", &self.year().year.to_string()))
+    }
+
+    fn select_year(&mut self, year: &str) -> Result<(), String> {
+        match self.years.iter().position(|y| y.year.to_string() == year) {
+            Some(i) => { self.year = i; Ok(()) },
+            None => Err(format!("unknown year: {}", year))
+        }
+    }
+
+    fn parser(&self, day: usize) -> Result<DayParser, String> {
+        self.year().day_parsers.get(day.wrapping_sub(1)).copied().ok_or(format!("no such day: {}", day))
+    }
+
+    fn load(&mut self, day: usize, source: String, input: String, expected: Vec<Solution>) -> Result<(), String> {
+        let year = self.year();
+        let parser = self.parser(day)?;
+        let key = (year.year, day, hash::fnv1a(input.as_bytes()));
+        let parsed = match self.cache.get(&key) {
+            Some((_, parsed)) => {
+                println!("y{:04} d{:02} {} (cached)", year.year, day, source);
+                parsed.clone()
+            },
+            None => {
+                let t = Instant::now();
+                let parsed = guard(year.year, day, 0, || OwnedDay::parse(parser, input))?;
+                let t = t.elapsed();
+                let parsed = Rc::new(parsed);
+                println!("y{:04} d{:02} {} parsed in {:.3} μs", year.year, day, source, t.as_nanos() as f32 / 1000.0);
+                self.cache.retain(|&(y, d, _), (s, _)| (y, d, s.as_str()) != (year.year, day, source.as_str()));
+                self.cache.insert(key, (source.clone(), parsed.clone()));
+                parsed
+            }
+        };
+        self.loaded = Some(Loaded { year: year.year, day, source, expected, parsed });
+        Ok(())
+    }
+
+    fn load_file(&mut self, day: usize, file: Option<&str>) -> Result<(), String> {
+        self.parser(day)?;
+        let dir = self.dir();
+        let default = dir.join(format!("{:02}.txt", day));
+        let path = file.map(PathBuf::from).unwrap_or_else(|| default.clone());
+        let input = input::read(&path, &self.key_file).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        // The answers in the solutions file only apply to the default inputs
+        let expected = if path == default {
+            solutions(&dir.join("solutions.txt")).get(day - 1).cloned().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        self.load(day, path.display().to_string(), input, expected)
+    }
+
+    fn paste(&mut self, day: usize, lines: &mut dyn Iterator<Item = String>) -> Result<(), String> {
+        let mut input = Vec::new();
+        for line in lines {
+            if line == "." { break }
+            input.push(line);
+        }
+        self.load(day, "pasted input".to_string(), input::normalise(input.join("\n")), Vec::new())
+    }
+
+    fn loaded(&self) -> Result<&Loaded, String> {
+        self.loaded.as_ref().ok_or_else(|| "no day loaded, use load or paste first".to_string())
+    }
+
+    // The selected part or all parts of the loaded day
+    fn parts(&self, part: Option<&str>) -> Result<Vec<usize>, String> {
+        let n = self.loaded()?.parsed.parts().len();
+        match part {
+            None => Ok((1..=n).collect()),
+            Some(p) => match p.parse() {
+                Ok(p) if p >= 1 && p <= n => Ok(vec![p]),
+                _ => Err(format!("no such part: {}", p))
+            }
+        }
+    }
+
+    fn run(&self, part: Option<&str>) -> Result<(), String> {
+        let loaded = self.loaded()?;
+        for p in self.parts(part)? {
+            let (actual, t) = loaded.with_part(p, |part| {
+                let t = Instant::now();
+                (part.solve(), t.elapsed().as_nanos())
+            })?;
+            let ok = loaded.expected.get(p - 1).map(|expected| expected.matches(actual));
+            print_part(ok, loaded.year, loaded.day, p, actual, &format!("{:14.3} μs", t as f32 / 1000.0));
+            if self.explain { self.explain(Some(&p.to_string()))? }
+        }
+        Ok(())
+    }
+
+    fn time(&self, part: Option<&str>, n: Option<&str>) -> Result<(), String> {
+        let loaded = self.loaded()?;
+        let n = match n {
+            Some(n) => n.parse().map_err(|_| format!("not a number: {}", n))?,
+            None => self.repeat
+        };
+        if n == 0 { return Err("cannot time 0 runs".to_string()) }
+        for p in self.parts(part)? {
+            let mut times: Vec<u128> = loaded.with_part(p, |part| (0..n).map(|_| {
+                let t = Instant::now();
+                part.solve();
+                t.elapsed().as_nanos()
+            }).collect())?;
+            times.sort_unstable();
+            let mean = times.iter().sum::<u128>() / n as u128;
+            println!("y{:04} d{:02} p{:02} {} runs: min {:.3} μs, median {:.3} μs, mean {:.3} μs, max {:.3} μs",
+                     loaded.year, loaded.day, p, n,
+                     times[0] as f32 / 1000.0, times[n / 2] as f32 / 1000.0, mean as f32 / 1000.0, times[n - 1] as f32 / 1000.0);
+        }
+        Ok(())
+    }
+
+    fn explain(&self, part: Option<&str>) -> Result<(), String> {
+        let loaded = self.loaded()?;
+        for p in self.parts(part)? {
+            let details = loaded.with_part(p, |part| part.explain())?;
+            if details.is_empty() {
+                println!("{:20}p{:02} has no details", "", p);
+            }
+            print_details(&details);
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, param: Option<&str>, value: Option<&str>) -> Result<(), String> {
+        let value = value.ok_or("missing value")?;
+        match param {
+            Some("repeat") => self.repeat = value.parse().map_err(|_| format!("not a number: {}", value))?,
+            Some("explain") => self.explain = match value {
+                "on" => true,
+                "off" => false,
+                _ => return Err(format!("expected on or off: {}", value))
+            },
+            Some("dir") => self.input = value.to_string(),
+            // The days read their puzzle parameters while solving, so the loaded day needn't be parsed again
+            Some(p) => match self.year().params.iter().find(|(_, param)| param.name == p) {
+                Some((_, param)) if value == "default" => param.reset(),
+                Some((_, param)) => param.set(value.parse().map_err(|_| format!("not a number: {}", value))?)?,
+                None => return Err(format!("unknown parameter: {}", p))
+            },
+            None => return Err("missing parameter".to_string())
+        }
+        Ok(())
+    }
+
+    fn params(&self) {
+        println!("year    {}", self.year().year);
+        println!("dir     {}", self.input);
+        println!("repeat  {}", self.repeat);
+        println!("explain {}", if self.explain { "on" } else { "off" });
+        if let Some(loaded) = &self.loaded {
+            println!("loaded  y{:04} d{:02} {}", loaded.year, loaded.day, loaded.source);
+        }
+        for (day, param) in &self.year().params {
+            println!("{:7} {} (d{:02}, default {})", param.name, param.get(), day, param.default);
+        }
+    }
+
+    fn command(&mut self, line: &str, lines: &mut dyn Iterator<Item = String>) -> Result<bool, String> {
+        let mut words = line.split_whitespace();
+        let cmd = match words.next() {
+            Some(cmd) => cmd,
+            None => return Ok(true)
+        };
+        let arg1 = words.next();
+        let arg2 = words.next();
+        let day = || -> Result<usize, String> {
+            let d = arg1.ok_or("missing day")?;
+            d.parse().map_err(|_| format!("not a day: {}", d))
+        };
+
+        match cmd {
+            "years" => for y in self.years { println!("{}", y.year) },
+            "year" => self.select_year(arg1.ok_or("missing year")?)?,
+            "days" => {
+                let year = self.year();
+                let days: Vec<String> = (1..=year.day_parsers.len()).map(|d| format!("d{:02}", d)).collect();
+                println!("y{:04}: {}", year.year, days.join(" "));
+            },
+            "load" => self.load_file(day()?, arg2)?,
+            "paste" => self.paste(day()?, lines)?,
+            "run" => self.run(arg1)?,
+            "time" => self.time(arg1, arg2)?,
+            "explain" => self.explain(arg1)?,
+            "set" => self.set(arg1, arg2)?,
+            "params" => self.params(),
+            "cache" => for ((year, day, key), (source, _)) in &self.cache {
+                println!("y{:04} d{:02} {:016x} {}", year, day, key, source)
+            },
+            "help" => println!("{}", HELP),
+            "quit" | "exit" => return Ok(false),
+            _ => return Err(format!("unknown command: {}, try help", cmd))
+        }
+        Ok(true)
+    }
+}
+
+pub fn repl(years: &[Year], opts: &Options) {
+    let mut session = Session {
+        years,
+        year: years.len() - 1,
+        input: opts.input.clone(),
+        key_file: opts.key_file.clone(),
+        repeat: opts.iterations,
+        explain: false,
+        cache: HashMap::new(),
+        loaded: None,
+    };
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines().map_while(Result::ok);
+    loop {
+        print!("aoc y{:04}> ", session.year().year);
+        io::stdout().flush().unwrap();
+        let line = match lines.next() {
+            Some(line) => line,
+            None => break
+        };
+        match session.command(&line, &mut lines) {
+            Ok(true) => (),
+            Ok(false) => break,
+            Err(e) => println!("error: {}", e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adventofcode2020::y2020;
+
+    fn session(years: &[Year]) -> Session<'_> {
+        Session {
+            years,
+            year: 0,
+            input: "input".to_string(),
+            key_file: PathBuf::from(".aoc/key"),
+            repeat: 10,
+            explain: false,
+            cache: HashMap::new(),
+            loaded: None,
+        }
+    }
+
+    fn paste(session: &mut Session, day: usize, input: &str) -> Rc<OwnedDay> {
+        let mut lines = input.lines().map(|l| l.to_string()).chain(Some(".".to_string()));
+        assert_eq!(session.command(&format!("paste {}", day), &mut lines), Ok(true));
+        session.loaded.as_ref().unwrap().parsed.clone()
+    }
+
+    #[test]
+    fn commands() {
+        let years = [y2020::year()];
+        let mut session = session(&years);
+        let mut none = std::iter::empty();
+        let mut run = |line: &str| session.command(line, &mut none);
+        assert_eq!(run(""), Ok(true));
+        assert_eq!(run("quit"), Ok(false));
+        assert_eq!(run("frobnicate"), Err("unknown command: frobnicate, try help".to_string()));
+        assert_eq!(run("load"), Err("missing day".to_string()));
+        assert_eq!(run("load x"), Err("not a day: x".to_string()));
+        assert_eq!(run("paste 26"), Err("no such day: 26".to_string()));
+        assert_eq!(run("load 0"), Err("no such day: 0".to_string()));
+        assert_eq!(run("load 26"), Err("no such day: 26".to_string()));
+        assert_eq!(run("year 1999"), Err("unknown year: 1999".to_string()));
+        assert_eq!(run("run"), Err("no day loaded, use load or paste first".to_string()));
+        assert_eq!(run("set repeat"), Err("missing value".to_string()));
+        assert_eq!(run("set repeat x"), Err("not a number: x".to_string()));
+        assert_eq!(run("set colour on"), Err("unknown parameter: colour".to_string()));
+        assert_eq!(run("set repeat 3"), Ok(true));
+        assert_eq!(run("set explain on"), Ok(true));
+        assert_eq!((session.repeat, session.explain), (3, true));
+    }
+
+    #[test]
+    fn puzzle_parameters() {
+        let years = [y2020::year()];
+        let mut session = session(&years);
+        let mut none = std::iter::empty();
+        assert_eq!(session.command("set preamble 1", &mut none), Err("preamble must be at least 2".to_string()));
+        assert_eq!(session.command("set preamble 5", &mut none), Ok(true));
+        let example = "35\n20\n15\n25\n47\n40\n62\n55\n65\n95\n102\n117\n150\n182\n127\n219\n299\n277\n309\n576";
+        let parsed = paste(&mut session, 9, example);
+        assert_eq!(parsed.parts()[0].solve(), 127);
+        assert_eq!(session.command("set preamble default", &mut none), Ok(true));
+        assert_eq!(years[0].params(9)[0].get(), 25);
+    }
+
+    #[test]
+    fn cached_inputs() {
+        let years = [y2020::year()];
+        let mut session = session(&years);
+        let first = paste(&mut session, 1, "1721\n979\n366\n299\n675\n1456");
+        // The same input again is not parsed again, even with different line endings
+        let again = paste(&mut session, 1, "1721\r\n979\r\n366\r\n299\r\n675\r\n1456");
+        assert!(Rc::ptr_eq(&first, &again));
+        assert_eq!(session.cache.len(), 1);
+
+        // A changed paste replaces the earlier one, other days are kept
+        paste(&mut session, 2, "1-3 a: abcde");
+        let changed = paste(&mut session, 1, "1010\n1010");
+        assert!(!Rc::ptr_eq(&first, &changed));
+        assert_eq!(session.cache.len(), 2);
+        assert_eq!(changed.parts()[0].solve(), 1010 * 1010);
+        // The loaded day keeps its parsed input even when evicted from the cache
+        assert_eq!(first.parts()[0].solve(), 514579);
+    }
+
+    #[test]
+    fn panics() {
+        let years = [y2020::year()];
+        let mut session = session(&years);
+        let mut lines = "1721\nx\n.".lines().map(|l| l.to_string());
+        assert_eq!(session.command("paste 1", &mut lines), Err("y2020 d01 parsing panicked".to_string()));
+        assert!(session.loaded.is_none() && session.cache.is_empty());
+
+        // No two entries add up to 2020
+        paste(&mut session, 1, "1\n2");
+        let mut none = std::iter::empty();
+        assert_eq!(session.command("run 1", &mut none), Err("y2020 d01 p01 panicked".to_string()));
+        assert_eq!(session.command("explain 1", &mut none), Err("y2020 d01 p01 panicked".to_string()));
+        // The session goes on
+        paste(&mut session, 1, "1721\n299");
+        assert_eq!(session.command("run 1", &mut none), Ok(true));
+    }
+}
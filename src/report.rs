@@ -0,0 +1,193 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::baseline::Baseline;
+use crate::{spoiler, DayResult};
+
+// The bar chart uses a logarithmic time axis from 100 ns to 10 s
+const CHART_MIN_EXP: f64 = 2.0;
+const CHART_MAX_EXP: f64 = 10.0;
+const CHART_LABEL_W: f64 = 50.0;
+const CHART_PLOT_W: f64 = 720.0;
+const BAR_H: f64 = 6.0;
+const ROW_H: f64 = 3.0 * BAR_H + 8.0;
+const BAR_COLOURS: [&str; 3] = ["#e0a000", "#3070d0", "#a040c0"];
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: right; }
+th { background: #eee; }
+.ok { color: #080; font-weight: bold; }
+.err { color: #c00; font-weight: bold; }
+.missing { color: #888; }
+.slower { color: #c00; }
+.faster { color: #080; }
+svg text { font-size: 11px; }";
+
+fn format_time(nanos: u128) -> String {
+    format!("{:.3} μs", nanos as f64 / 1000.0)
+}
+
+// Slowdowns over the threshold ratio compared to the baseline are highlighted
+fn baseline_cell(baseline: &Baseline, threshold: f64, key: (u16, usize, usize), nanos: u128) -> String {
+    match baseline.get(&key) {
+        None => "<td class=\"missing\">-</td>".to_string(),
+        Some(&base) => {
+            let delta = (nanos as f64 - base as f64) / base.max(1) as f64;
+            let class = if delta > threshold {
+                "slower"
+            } else if delta < -threshold {
+                "faster"
+            } else {
+                ""
+            };
+            format!("<td class=\"{}\">{} ({:+.1}%)</td>", class, format_time(base), delta * 100.0)
+        }
+    }
+}
+
+fn chart(results: &[DayResult]) -> String {
+    let x = |nanos: u128| {
+        let exp = (nanos.max(1) as f64).log10().clamp(CHART_MIN_EXP, CHART_MAX_EXP);
+        CHART_PLOT_W * (exp - CHART_MIN_EXP) / (CHART_MAX_EXP - CHART_MIN_EXP)
+    };
+    let h = ROW_H * results.len() as f64 + 20.0;
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n", CHART_LABEL_W + CHART_PLOT_W + 20.0, h);
+
+    // Grid lines at every decade
+    let mut exp = CHART_MIN_EXP;
+    while exp <= CHART_MAX_EXP {
+        let gx = CHART_LABEL_W + x(10f64.powf(exp) as u128);
+        let label = match exp as u32 {
+            2 => "100 ns", 3 => "1 μs", 4 => "10 μs", 5 => "100 μs", 6 => "1 ms",
+            7 => "10 ms", 8 => "100 ms", 9 => "1 s", _ => "10 s"
+        };
+        svg.push_str(&format!("<line x1=\"{0}\" y1=\"0\" x2=\"{0}\" y2=\"{1}\" stroke=\"#ddd\"/>\n", gx, h - 15.0));
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>\n", gx, h - 3.0, label));
+        exp += 1.0;
+    }
+
+    for (row, r) in results.iter().enumerate() {
+        let y = ROW_H * row as f64;
+        svg.push_str(&format!("<text x=\"0\" y=\"{}\">d{:02}</text>\n", y + 2.0 * BAR_H, r.day));
+        let bars = r.parse_nanos.iter().chain(r.parts.iter().map(|p| &p.nanos));
+        for (i, &nanos) in bars.enumerate() {
+            let what = if i == 0 { "parsing".to_string() } else { format!("p{:02}", i) };
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" fill=\"{}\"><title>d{:02} {} {}</title></rect>\n",
+                CHART_LABEL_W, y + BAR_H * i as f64, x(nanos).max(1.0), BAR_H - 1.0, BAR_COLOURS[i.min(2)],
+                r.day, what, format_time(nanos)));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn year_section(year: u16, results: &[DayResult], baseline: &Baseline, threshold: f64) -> String {
+    let has_baseline = !baseline.is_empty();
+    let mut html = format!("<h2 id=\"y{0}\">{0}</h2>\n", year);
+    html.push_str(&chart(results));
+    html.push_str("<table>\n<tr><th>Day</th><th>Part</th><th>Status</th><th>Answer</th><th>Expected</th><th>Time</th>");
+    if has_baseline { html.push_str("<th>Baseline</th>") }
+    html.push_str("</tr>\n");
+
+    for r in results {
+        match r.parse_nanos {
+            None => {
+                html.push_str(&format!("<tr><td>{:02}</td><td></td><td class=\"missing\">no input</td><td></td><td></td><td></td>", r.day));
+                if has_baseline { html.push_str("<td></td>") }
+                html.push_str("</tr>\n");
+            },
+            Some(nanos) => {
+                html.push_str(&format!("<tr><td>{:02}</td><td>parsing</td><td></td><td></td><td></td><td>{}</td>", r.day, format_time(nanos)));
+                if has_baseline { html.push_str(&baseline_cell(baseline, threshold, (year, r.day, 0), nanos)) }
+                html.push_str("</tr>\n");
+            }
+        }
+        for p in &r.parts {
+            let status = if p.ok() { "<td class=\"ok\">OK</td>" } else { "<td class=\"err\">ERR</td>" };
+            html.push_str(&format!("<tr><td>{:02}</td><td>{:02}</td>{}<td>{}</td><td>{}</td><td>{}</td>",
+                                   r.day, p.part, status, spoiler::answer(p.actual), spoiler::expected(&p.expected), format_time(p.nanos)));
+            if has_baseline { html.push_str(&baseline_cell(baseline, threshold, (year, r.day, p.part), p.nanos)) }
+            html.push_str("</tr>\n");
+        }
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+// Write a self-contained HTML report of the results of the given years
+pub fn write_html(to: &Path, years: &[(u16, &[DayResult])], baseline: &Baseline, threshold: f64) -> io::Result<()> {
+    let mut html = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Advent of Code results</title>\n<style>\n{}\n</style>\n</head>\n<body>\n", STYLE);
+    html.push_str("<h1>Advent of Code results</h1>\n<h2>Totals</h2>\n<table>\n<tr><th>Year</th><th>Passed</th><th>Failed</th><th>Missing inputs</th><th>Parsing</th><th>Solving</th><th>Total</th></tr>\n");
+    for (year, results) in years {
+        let passed: usize = results.iter().map(|r| r.parts.iter().filter(|p| p.ok()).count()).sum();
+        let failed: usize = results.iter().map(|r| r.parts.iter().filter(|p| !p.ok()).count()).sum();
+        let missing = results.iter().filter(|r| r.parse_nanos.is_none()).count();
+        let parsing: u128 = results.iter().filter_map(|r| r.parse_nanos).sum();
+        let total: u128 = results.iter().map(|r| r.nanos()).sum();
+        html.push_str(&format!("<tr><td><a href=\"#y{0}\">{0}</a></td><td>{1}</td><td>{2}</td><td>{3}</td><td>{4}</td><td>{5}</td><td>{6}</td></tr>\n",
+                               year, passed, failed, missing, format_time(parsing), format_time(total - parsing), format_time(total)));
+    }
+    html.push_str("</table>\n");
+    for (year, results) in years {
+        html.push_str(&year_section(*year, results, baseline, threshold));
+    }
+    html.push_str("</body>\n</html>\n");
+    fs::write(to, html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use crate::baseline::tests::result;
+    use crate::spoiler::Solution;
+
+    #[test]
+    fn regressions() {
+        let baseline: Baseline = [((2020, 1, 1), 1000)].iter().copied().collect();
+        let cell = |nanos| baseline_cell(&baseline, 0.1, (2020, 1, 1), nanos);
+        assert_eq!(cell(1200), "<td class=\"slower\">1.000 μs (+20.0%)</td>");
+        assert_eq!(cell(1050), "<td class=\"\">1.000 μs (+5.0%)</td>");
+        assert_eq!(cell(800), "<td class=\"faster\">1.000 μs (-20.0%)</td>");
+        assert_eq!(baseline_cell(&baseline, 0.1, (2020, 1, 2), 1200), "<td class=\"missing\">-</td>");
+    }
+
+    #[test]
+    fn chart_bars() {
+        let svg = chart(&[result(1, 1, &[1000, 10_000_000_000]), result(2, 100_000, &[])]);
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect ").count(), 4);
+        assert_eq!(svg.matches("<line ").count(), 9);
+        // Too short bars are still visible, the scale ends at 10 s
+        assert!(svg.contains("<rect x=\"50\" y=\"0\" width=\"1.0\" height=\"5\" fill=\"#e0a000\"><title>d01 parsing 0.001 μs</title></rect>"));
+        assert!(svg.contains("width=\"90.0\" height=\"5\" fill=\"#3070d0\"><title>d01 p01 1.000 μs</title>"));
+        assert!(svg.contains("width=\"720.0\" height=\"5\" fill=\"#a040c0\"><title>d01 p02 10000000.000 μs</title>"));
+        assert!(svg.contains("<text x=\"0\" y=\"38\">d02</text>"));
+    }
+
+    #[test]
+    fn html_report() {
+        let path = env::temp_dir().join(format!("aoc-report-test-{}.html", std::process::id()));
+        let mut wrong = result(2, 100, &[300]);
+        wrong.parts[0].expected = Solution::parse("43").unwrap();
+        let results = [result(1, 100, &[200]), wrong, DayResult { day: 3, parse_nanos: None, parse_spans: Vec::new(), parts: Vec::new(), cached: false }];
+
+        write_html(&path, &[(2020, &results)], &Baseline::new(), 0.1).unwrap();
+        let html = fs::read_to_string(&path).unwrap();
+        assert!(html.contains("<tr><td><a href=\"#y2020\">2020</a></td><td>1</td><td>1</td><td>1</td><td>0.200 μs</td><td>0.500 μs</td><td>0.700 μs</td></tr>"));
+        assert!(html.contains("<h2 id=\"y2020\">2020</h2>\n<svg "));
+        assert!(html.contains("<tr><td>02</td><td>01</td><td class=\"err\">ERR</td>"));
+        assert!(html.contains("<tr><td>03</td><td></td><td class=\"missing\">no input</td><td></td><td></td><td></td></tr>"));
+        assert!(!html.contains("<th>Baseline</th>"));
+
+        let baseline: Baseline = [((2020, 1, 1), 100)].iter().copied().collect();
+        write_html(&path, &[(2020, &results)], &baseline, 0.1).unwrap();
+        let html = fs::read_to_string(&path).unwrap();
+        assert!(html.contains("<th>Baseline</th>"));
+        assert!(html.contains("<td>0.200 μs</td><td class=\"slower\">0.100 μs (+100.0%)</td></tr>"));
+        fs::remove_file(&path).unwrap();
+    }
+}
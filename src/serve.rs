@@ -0,0 +1,310 @@
+use std::env;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use adventofcode2020::log;
+use crate::json::Json;
+use crate::{external, input, usage_error, Options, Year};
+
+// Limits of the requests, besides the configurable size of the input
+const MAX_HEAD: usize = 16 * 1024;
+// The whole request, input included, must arrive within this
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_CONNECTIONS: usize = 8;
+
+// Solve an input with the registered day as JSON: the answers of the parts and how long parsing and solving took
+pub fn solve(year: &Year, day: usize, input: &str) -> Result<Json, String> {
+    let parser = year.day_parsers.get(day.wrapping_sub(1)).ok_or(format!("no such day: {}", day))?;
+    log::enter(year.year, day, 0);
+    let t = Instant::now();
+    let parsed = parser(input);
+    let parse_nanos = t.elapsed().as_nanos();
+    let parts = parsed.parts.iter().enumerate().map(|(p, part)| {
+        log::enter(year.year, day, p + 1);
+        let t = Instant::now();
+        let answer = part.solve();
+        let nanos = t.elapsed().as_nanos();
+        log::leave();
+        Json::object(vec![("part", Json::Int(p as i64 + 1)), ("answer", Json::Int(answer)), ("nanos", Json::Int(nanos as i64))])
+    }).collect();
+    Ok(Json::object(vec![("year", Json::Int(year.year as i64)),
+                         ("day", Json::Int(day as i64)),
+                         ("parse_nanos", Json::Int(parse_nanos as i64)),
+                         ("parts", Json::Array(parts))]))
+}
+
+// The solve command: solve YEAR DAY with the input on stdin, print the result as JSON.
+// The server runs this in a child process, so a request that takes too long can be killed.
+pub fn solve_command(years: &[Year], args: &[String]) {
+    let (year, day): (u16, usize) = match args {
+        [year, day] => match (year.parse(), day.parse()) {
+            (Ok(year), Ok(day)) => (year, day),
+            _ => usage_error("usage: solve YEAR DAY < INPUT")
+        },
+        _ => usage_error("usage: solve YEAR DAY < INPUT")
+    };
+    let year = years.iter().find(|y| y.year == year).unwrap_or_else(|| usage_error(&format!("no such year: {}", year)));
+    let mut raw = String::new();
+    io::stdin().read_to_string(&mut raw).unwrap_or_else(|e| usage_error(&format!("cannot read the input: {}", e)));
+    match solve(year, day, &input::normalise(raw)) {
+        Ok(json) => println!("{}", json),
+        Err(e) => usage_error(&e)
+    }
+}
+
+pub struct Config {
+    // The days registered in each year
+    pub years: Vec<(u16, usize)>,
+    // The solve command, the year and day are appended to it
+    pub solver: Vec<String>,
+    pub timeout: Duration,
+    pub max_input: usize,
+    pub request_timeout: Duration,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+type Error = (u16, String);
+
+// Reads from a stream until a deadline: a read timeout alone would let a client send a byte at a time forever
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "the request took too long"))
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+fn read_request(stream: &TcpStream, max_input: usize, deadline: Instant) -> Result<Request, Error> {
+    let mut reader = BufReader::new(Deadline { stream, deadline });
+    let mut head = 0;
+    let mut read_line = || {
+        let mut line = String::new();
+        match reader.by_ref().take((MAX_HEAD - head) as u64 + 1).read_line(&mut line) {
+            Ok(n) => {
+                head += n;
+                if head > MAX_HEAD { return Err((431, "request header too large".to_string())) }
+                if !line.ends_with('\n') { return Err((400, "incomplete request".to_string())) }
+                Ok(line.trim_end().to_string())
+            },
+            Err(e) => Err((408, e.to_string()))
+        }
+    };
+
+    let line = read_line()?;
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let (method, path) = match fields.as_slice() {
+        [method, path, version] if version.starts_with("HTTP/1.") => (method.to_string(), path.to_string()),
+        _ => return Err((400, "bad request line".to_string()))
+    };
+    let mut length = None;
+    loop {
+        let line = read_line()?;
+        if line.is_empty() { break }
+        let (name, value) = line.split_once(':').ok_or((400, "bad header".to_string()))?;
+        if name.eq_ignore_ascii_case("content-length") {
+            length = Some(value.trim().parse::<usize>().map_err(|_| (400, "bad content length".to_string()))?);
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err((411, "send the input with a content length".to_string()))
+        }
+    }
+
+    let length = match (method.as_str(), length) {
+        ("POST", None) => return Err((411, "send the input with a content length".to_string())),
+        (_, length) => length.unwrap_or(0)
+    };
+    if length > max_input {
+        return Err((413, format!("the input is over {} bytes", max_input)))
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|e| (408, e.to_string()))?;
+    let body = String::from_utf8(body).map_err(|_| (400, "the input is not UTF-8".to_string()))?;
+    Ok(Request { method, path, body })
+}
+
+fn error(message: &str) -> String {
+    Json::object(vec![("error", Json::Str(message.to_string()))]).to_string()
+}
+
+fn days(year: u16, days: usize) -> Json {
+    Json::object(vec![("year", Json::Int(year as i64)),
+                      ("days", Json::Array((1..=days as i64).map(Json::Int).collect()))])
+}
+
+// GET /years lists the years and their days, GET /YEAR lists the days of a year, POST /YEAR/DAY solves the input in the body
+fn route(config: &Config, req: &Request) -> (u16, String) {
+    let segments: Vec<&str> = req.path.trim_matches('/').split('/').collect();
+    let year = |s: &str| s.parse::<u16>().ok().and_then(|y| config.years.iter().find(|(year, _)| *year == y)).copied();
+    match (req.method.as_str(), segments.as_slice()) {
+        ("GET", ["years"]) => (200, Json::object(vec![("years", Json::Array(config.years.iter().map(|&(y, d)| days(y, d)).collect()))]).to_string()),
+        ("GET", [y]) => match year(y) {
+            Some((y, d)) => (200, days(y, d).to_string()),
+            None => (404, error("no such year"))
+        },
+        ("POST", [y, d]) => match (year(y), d.parse::<usize>()) {
+            (Some((y, days)), Ok(d)) if d >= 1 && d <= days => {
+                let mut command = Command::new(&config.solver[0]);
+                command.args(&config.solver[1..]).arg(y.to_string()).arg(d.to_string());
+                match external::output(command, &req.body, config.timeout) {
+                    Ok(out) => (200, out.trim_end().to_string()),
+                    Err(e @ external::Error::TimedOut(_)) => (503, error(&e.to_string())),
+                    Err(e) => (422, error(&format!("cannot solve the input, the solver {}", e)))
+                }
+            },
+            _ => (404, error("no such day"))
+        },
+        (_, ["years"]) | (_, [_]) | (_, [_, _]) => (405, error("method not allowed")),
+        _ => (404, error("not found"))
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Error"
+    }
+}
+
+fn handle(mut stream: TcpStream, config: &Config) {
+    let t = Instant::now();
+    let (line, (status, body)) = match read_request(&stream, config.max_input, t + config.request_timeout) {
+        Ok(req) => (format!("{} {}", req.method, req.path), route(config, &req)),
+        Err((status, e)) => ("-".to_string(), (status, error(&e)))
+    };
+    let _ = write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                   status, reason(status), body.len(), body);
+    println!("serve: {} {} {:.3} ms", line, status, t.elapsed().as_secs_f64() * 1000.0);
+}
+
+// Serve requests on the listener, each connection on its own thread
+pub fn serve_on(listener: TcpListener, config: Config) {
+    let config = Arc::new(config);
+    let connections = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming().flatten() {
+        if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            connections.fetch_sub(1, Ordering::SeqCst);
+            let body = error("too many requests in progress");
+            let mut stream = stream;
+            let _ = write!(stream, "HTTP/1.1 503 Service Unavailable\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+            continue
+        }
+        let (config, connections) = (config.clone(), connections.clone());
+        thread::spawn(move || {
+            handle(stream, &config);
+            connections.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+}
+
+pub fn serve(years: &[Year], opts: &Options) {
+    let listener = TcpListener::bind(&opts.listen).unwrap_or_else(|e| usage_error(&format!("cannot listen on {}: {}", opts.listen, e)));
+    let exe = env::current_exe().unwrap_or_else(|e| usage_error(&format!("cannot find the runner: {}", e)));
+    let mut solver = vec![exe.display().to_string(), "solve".to_string()];
+    // The solvers log like the server was told to, into the server's stderr or log file
+    for filter in &opts.log {
+        solver.extend(["--log".to_string(), filter.to_string()]);
+    }
+    if let Some(path) = &opts.log_file {
+        solver.extend(["--log-file".to_string(), path.display().to_string()]);
+    }
+    let config = Config {
+        years: years.iter().map(|y| (y.year, y.day_parsers.len())).collect(),
+        solver,
+        timeout: opts.serve_timeout,
+        max_input: opts.max_input,
+        request_timeout: REQUEST_TIMEOUT,
+    };
+    println!("serve: listening on http://{}", listener.local_addr().map(|a| a.to_string()).unwrap_or_default());
+    serve_on(listener, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http;
+    use adventofcode2020::y2020;
+
+    #[test]
+    fn solve_example() {
+        let json = solve(&y2020::year(), 1, "1721\n979\n366\n299\n675\n1456").unwrap();
+        assert_eq!(json.to_string().split("\"nanos\"").count(), 3);
+        assert!(json.to_string().starts_with(r#"{"year":2020,"day":1,"parse_nanos":"#));
+        assert!(json.to_string().contains(r#"{"part":1,"answer":514579,"nanos":"#));
+        assert!(json.to_string().contains(r#"{"part":2,"answer":241861950,"nanos":"#));
+        assert!(solve(&y2020::year(), 26, "").is_err());
+    }
+
+    #[test]
+    fn serve_with_limits() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        // A stand-in for the solve command: it echoes the year, day and input, or sleeps forever on a "slow" input
+        let solver = r#"read input; [ "$input" = slow ] && sleep 10; echo "{\"year\":$0,\"day\":$1,\"input\":\"$input\"}""#;
+        let config = Config {
+            years: vec![(2020, 15)],
+            solver: vec!["sh".to_string(), "-c".to_string(), solver.to_string()],
+            timeout: Duration::from_millis(200),
+            max_input: 100,
+            request_timeout: Duration::from_secs(5),
+        };
+        thread::spawn(move || serve_on(listener, config));
+
+        let get = |path: &str| http::get(&format!("{}{}", url, path), &[]).unwrap();
+        let post = |path: &str, body: &str| http::post(&format!("{}{}", url, path), &[], body).unwrap();
+        let res = get("/years");
+        assert_eq!((res.status, res.body.as_str()), (200, r#"{"years":[{"year":2020,"days":[1,2,3,4,5,6,7,8,9,10,11,12,13,14,15]}]}"#));
+        assert_eq!(get("/2020").status, 200);
+        assert_eq!(get("/2019").status, 404);
+        let res = post("/2020/3", "fast\n");
+        assert_eq!((res.status, res.body.as_str()), (200, r#"{"year":2020,"day":3,"input":"fast"}"#));
+        assert_eq!(post("/2020/16", "").status, 404);
+        assert_eq!(get("/2020/3").status, 405);
+        assert_eq!(post("/2020/3", &"x".repeat(101)).status, 413);
+        let t = Instant::now();
+        assert_eq!(post("/2020/3", "slow\n").status, 503);
+        assert!(t.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn slow_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        // A byte every 20 ms never hits a read timeout, only the deadline of the whole request
+        thread::spawn(move || {
+            for b in b"GET /years HTTP/1.1\r\nX-Slow: ".iter().chain([b'x'; 100].iter()) {
+                if client.write_all(&[*b]).is_err() { break }
+                thread::sleep(Duration::from_millis(20));
+            }
+        });
+        let t = Instant::now();
+        let err = read_request(&server, 100, t + Duration::from_millis(300)).err().unwrap();
+        assert_eq!(err.0, 408);
+        assert!(t.elapsed() < Duration::from_secs(1));
+    }
+}
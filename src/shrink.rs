@@ -0,0 +1,300 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Once};
+use std::thread;
+use std::time::Duration;
+use adventofcode2020::{Alternate, DayParser};
+use crate::{external, input, selected_years, spoiler, usage_error, Options};
+
+// How our part fared on an input
+#[derive(Clone, Debug, PartialEq)]
+enum Run {
+    Answer(i64),
+    // Where it panicked
+    Panic(String),
+    Timeout,
+}
+
+// The failure kept while shrinking: a panic at the same place, or a different answer from the other implementation
+#[derive(Clone, Debug, PartialEq)]
+enum Failure {
+    Panic(String),
+    Disagree,
+}
+
+// What the part is compared with: an external solver, or a second implementation of the part in this crate
+enum Other<'a> {
+    External(&'a str),
+    Alternate(&'a Alternate),
+}
+
+impl Other<'_> {
+    fn answer(&self, part: usize, input: &str, timeout: Duration) -> Option<i64> {
+        match self {
+            Other::External(cmd) => theirs(cmd, part, input, timeout),
+            Other::Alternate(alternate) => {
+                let (parser, input) = (alternate.parser, input.to_string());
+                match run(move || parser(&input).solve(), timeout) {
+                    Run::Answer(answer) => Some(answer),
+                    _ => None
+                }
+            }
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Other::External(cmd) => cmd.to_string(),
+            Other::Alternate(alternate) => format!("the {} implementation", alternate.name)
+        }
+    }
+}
+
+thread_local! {
+    // Where the part running on this thread panicked, recorded by the hook
+    static PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+    // Set on the threads running the parts, whose panics are expected
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+// The panics are expected, don't print them on every try. The hook is installed once and never removed: it only
+// records the panics of the threads running the parts, and hands the others (like those of the tests running next
+// to the shrinking ones) to the previous hook.
+fn install_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if QUIET.with(|q| q.get()) {
+                PANIC.with(|p| *p.borrow_mut() = info.location().map(|l| l.to_string()));
+            } else {
+                previous(info)
+            }
+        }));
+    });
+}
+
+// Solve on a thread of its own, so it can be abandoned if it takes too long
+fn run<F: FnOnce() -> i64 + Send + 'static>(solve: F, timeout: Duration) -> Run {
+    install_hook();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        QUIET.with(|q| q.set(true));
+        let run = match panic::catch_unwind(AssertUnwindSafe(solve)) {
+            Ok(answer) => Run::Answer(answer),
+            Err(_) => Run::Panic(PANIC.with(|p| p.borrow_mut().take()).unwrap_or_default())
+        };
+        let _ = tx.send(run);
+    });
+    // A part stuck on an input keeps running in the background until the command exits
+    rx.recv_timeout(timeout).unwrap_or(Run::Timeout)
+}
+
+fn ours(parser: DayParser, part: usize, input: String, timeout: Duration) -> Run {
+    run(move || parser(&input).parts[part - 1].solve(), timeout)
+}
+
+fn theirs(cmd: &str, part: usize, input: &str, timeout: Duration) -> Option<i64> {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(cmd).stderr(Stdio::null());
+    let out = external::output(command, input, timeout).ok()?;
+    external::answers(&out).ok()?.get(part - 1).copied()
+}
+
+fn failure(parser: DayParser, part: usize, other: Option<&Other>, input: &str, timeout: Duration) -> Option<Failure> {
+    match ours(parser, part, input.to_string(), timeout) {
+        Run::Panic(at) => Some(Failure::Panic(at)),
+        Run::Answer(answer) => other.and_then(|other| other.answer(part, input, timeout)).filter(|&a| a != answer).map(|_| Failure::Disagree),
+        Run::Timeout => None
+    }
+}
+
+// The smallest sublist of the units (joined with the separator) that the test still accepts, by delta debugging:
+// try removing ever smaller chunks of the units, until no single unit can be removed
+pub fn ddmin<'a>(mut units: Vec<&'a str>, sep: &str, test: &mut dyn FnMut(&str) -> bool) -> Vec<&'a str> {
+    let mut n = 2;
+    while units.len() >= 2 {
+        let chunk = units.len().div_ceil(n);
+        let mut reduced = false;
+        for start in (0..units.len()).step_by(chunk) {
+            let end = (start + chunk).min(units.len());
+            // The chunk alone first, then everything but the chunk
+            let subset = units[start..end].to_vec();
+            let complement: Vec<&str> = units[..start].iter().chain(&units[end..]).copied().collect();
+            if subset.len() < units.len() && test(&subset.join(sep)) {
+                units = subset;
+                n = 2;
+            } else if n > 2 && test(&complement.join(sep)) {
+                units = complement;
+                n -= 1;
+            } else {
+                continue
+            }
+            reduced = true;
+            break
+        }
+        if !reduced {
+            if n >= units.len() { break }
+            n = (n * 2).min(units.len());
+        }
+    }
+    units
+}
+
+// Shrink the input of a part while it keeps panicking the same way, or disagreeing with the external solver or
+// the alternate implementation given with --against: shrink DAY PART [OUTPUT]
+pub fn shrink(opts: &Options, args: &[String]) {
+    let usage = "usage: shrink DAY PART [OUTPUT]";
+    let (day, part): (usize, usize) = match args {
+        [day, part] | [day, part, _] => match (day.parse(), part.parse()) {
+            (Ok(day), Ok(part)) if day > 0 && part > 0 => (day, part),
+            _ => usage_error(usage)
+        },
+        _ => usage_error(usage)
+    };
+    let years = selected_years(opts);
+    if years.len() > 1 { usage_error("select a single year with --year") }
+    let year = &years[0];
+    let parser = *year.day_parsers.get(day - 1).unwrap_or_else(|| usage_error(&format!("no such day: {}", day)));
+    let path = opts.input_dir(year.year).join(format!("{:02}.txt", day));
+    let original = input::read(&path, &opts.key_file).unwrap_or_else(|e| usage_error(&format!("{}: {}", path.display(), e)));
+    let timeout = opts.external_timeout;
+    let label = format!("y{:04} d{:02} p{:02}", year.year, day, part);
+    let other = match &opts.against {
+        Some(name) => {
+            let alternates = year.alternates(day, part);
+            match alternates.iter().find(|a| a.name == name) {
+                Some(alternate) => Some(Other::Alternate(alternate)),
+                None if alternates.is_empty() => usage_error(&format!("{} has no alternate implementations", label)),
+                None => {
+                    let names: Vec<&str> = alternates.iter().map(|a| a.name).collect();
+                    usage_error(&format!("{} has no alternate implementation {}, only {}", label, name, names.join(", ")))
+                }
+            }
+        },
+        None => opts.external(year.year, day).map(|cmd| Other::External(cmd))
+    };
+
+    let count = original.clone();
+    if matches!(run(move || parser(&count).parts.len() as i64, timeout), Run::Answer(n) if part as i64 > n) {
+        usage_error(&format!("no such part: {}", part))
+    }
+    let target = match failure(parser, part, other.as_ref(), &original, timeout) {
+        Some(target) => target,
+        None => match &other {
+            Some(other) => usage_error(&format!("{}: the part doesn't panic and agrees with {}", label, other.name())),
+            None => usage_error(&format!("{}: the part doesn't panic, use --external or --against to compare it with another solver", label))
+        }
+    };
+    match (&target, &other) {
+        (Failure::Panic(at), _) => println!("{} panics at {}", label, at),
+        (Failure::Disagree, Some(other)) => println!("{} disagrees with {}", label, other.name()),
+        (Failure::Disagree, None) => unreachable!()
+    }
+
+    // The same candidates come up again and again
+    let mut tried: HashMap<String, bool> = HashMap::new();
+    let mut test = |input: &str| {
+        if let Some(&res) = tried.get(input) { return res }
+        let res = failure(parser, part, other.as_ref(), input, timeout).as_ref() == Some(&target);
+        tried.insert(input.to_string(), res);
+        res
+    };
+    // Blank line separated records first (if there are any), then single lines
+    let records = ddmin(original.split("\n\n").collect(), "\n\n", &mut test).join("\n\n");
+    let lines = ddmin(records.split('\n').collect(), "\n", &mut test).join("\n");
+
+    let output = args.get(2).map_or_else(|| PathBuf::from(format!(".aoc/shrunk-y{:04}-d{:02}-p{:02}.txt", year.year, day, part)), PathBuf::from);
+    if let Some(dir) = output.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Err(e) = fs::write(&output, format!("{}\n", lines)) {
+        usage_error(&format!("cannot write {}: {}", output.display(), e))
+    }
+    println!("{} shrunk from {} to {} lines in {} tries: {}", label, original.lines().count(), lines.lines().count(), tried.len(), output.display());
+    if let (Failure::Disagree, Some(other)) = (target, other) {
+        if let Run::Answer(answer) = ours(parser, part, lines.clone(), timeout) {
+            let theirs = other.answer(part, &lines, timeout).map_or("-".to_string(), spoiler::answer);
+            println!("{} ours {}, theirs {}", label, spoiler::answer(answer), theirs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adventofcode2020::Part;
+
+    #[test]
+    fn ddmin_finds_minimal_input() {
+        let input: Vec<String> = (1..=100).map(|i| i.to_string()).collect();
+        let units: Vec<&str> = input.iter().map(|s| s.as_str()).collect();
+        let mut tries = 0;
+        // Fails if both 13 and 77 are there, or when there's 42 but not 41
+        let mut test = |s: &str| {
+            tries += 1;
+            let has = |n: &str| s.split(',').any(|u| u == n);
+            (has("13") && has("77")) || (has("42") && !has("41"))
+        };
+        let min = ddmin(units.clone(), ",", &mut test);
+        assert!(min == ["42"] || min == ["13", "77"], "{:?}", min);
+        assert!(tries < 200, "{} tries", tries);
+
+        let mut test = |s: &str| s.contains("99");
+        assert_eq!(ddmin(units, ",", &mut test), ["99"]);
+    }
+
+    #[test]
+    fn shrink_a_panic() {
+        let parser: DayParser = adventofcode2020::y2020::year().day_parsers[0];
+        let timeout = Duration::from_secs(5);
+        // d01 has no pair adding up to 2020 among these
+        let input = "1\n2\n3\n4\n5";
+        let target = ours(parser, 1, input.to_string(), timeout);
+        assert!(matches!(target, Run::Panic(_)));
+        let min = ddmin(input.split('\n').collect(), "\n", &mut |s| ours(parser, 1, s.to_string(), timeout) == target);
+        assert_eq!(min.len(), 1);
+        assert_eq!(ours(parser, 1, "1010\n1010".to_string(), timeout), Run::Answer(1010 * 1010));
+    }
+
+    // d01 p1 going wrong when the pair is the first two numbers
+    struct Wrong(Vec<i64>);
+
+    impl Part for Wrong {
+        fn solve(&self) -> i64 {
+            let n = &self.0;
+            if n.len() >= 2 && n[0] + n[1] == 2020 { return 0 }
+            (0..n.len()).flat_map(|i| (i + 1..n.len()).map(move |j| (i, j))).find(|&(i, j)| n[i] + n[j] == 2020)
+                .map(|(i, j)| n[i] * n[j]).unwrap()
+        }
+    }
+
+    fn parse_wrong(s: &str) -> Box<dyn Part + '_> {
+        Box::new(Wrong(s.lines().map(|l| l.parse().unwrap()).collect()))
+    }
+
+    #[test]
+    fn shrink_a_disagreement() {
+        let year = adventofcode2020::y2020::year();
+        let timeout = Duration::from_secs(5);
+        // The alternates agree with the parts on the real inputs
+        for (day, part, name) in [(8, 2, "brute"), (14, 2, "brute")] {
+            let alternate = year.alternates(day, part).into_iter().find(|a| a.name == name).unwrap();
+            let input = input::read(&PathBuf::from(format!("input/{:02}.txt", day)), &PathBuf::from(".aoc/key")).unwrap();
+            assert_eq!(failure(year.day_parsers[day - 1], part, Some(&Other::Alternate(alternate)), &input, timeout), None, "d{:02}", day);
+        }
+
+        let wrong = Alternate { day: 1, part: 1, name: "wrong", parser: parse_wrong };
+        let other = Some(Other::Alternate(&wrong));
+        let parser = year.day_parsers[0];
+        let input = "1721\n299\n979\n366\n675\n1456\n5";
+        assert_eq!(failure(parser, 1, other.as_ref(), "5\n1721\n299", timeout), None);
+        assert_eq!(failure(parser, 1, other.as_ref(), input, timeout), Some(Failure::Disagree));
+        let min = ddmin(input.split('\n').collect(), "\n", &mut |s| failure(parser, 1, other.as_ref(), s, timeout) == Some(Failure::Disagree));
+        assert_eq!(min, ["1721", "299"]);
+    }
+}
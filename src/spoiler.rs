@@ -0,0 +1,137 @@
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::SystemTime;
+use crate::{crypto, hash};
+
+// How answers are shown in the output
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Show,
+    Mask,
+    Hash,
+}
+
+impl Mode {
+    pub fn parse(s: &str) -> Option<Mode> {
+        match s {
+            "show" => Some(Mode::Show),
+            "mask" => Some(Mode::Mask),
+            "hash" => Some(Mode::Hash),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Show => "show",
+            Mode::Mask => "mask",
+            Mode::Hash => "hash"
+        }
+    }
+}
+
+// An expected answer, either as it is or as a salted hash: "hash:SALT:HASH" in solutions.txt
+#[derive(Clone, Debug, PartialEq)]
+pub enum Solution {
+    Answer(i64),
+    Hashed { salt: String, hash: u64 },
+}
+
+impl Solution {
+    pub fn parse(s: &str) -> Option<Solution> {
+        match s.strip_prefix("hash:") {
+            Some(s) => {
+                let i = s.find(':')?;
+                Some(Solution::Hashed { salt: s[..i].to_string(), hash: u64::from_str_radix(&s[i + 1..], 16).ok()? })
+            },
+            None => s.parse().ok().map(Solution::Answer)
+        }
+    }
+
+    // The solution hashed with a fresh random salt
+    pub fn hashed(&self) -> Solution {
+        match self {
+            Solution::Answer(answer) => {
+                let salt = random_salt();
+                Solution::Hashed { hash: salted(&salt, *answer), salt }
+            },
+            hashed => hashed.clone()
+        }
+    }
+
+    pub fn matches(&self, answer: i64) -> bool {
+        match self {
+            Solution::Answer(expected) => *expected == answer,
+            Solution::Hashed { salt, hash } => salted(salt, answer) == *hash
+        }
+    }
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Solution::Answer(answer) => write!(f, "{}", answer),
+            Solution::Hashed { salt, hash } => write!(f, "hash:{}:{:016x}", salt, hash)
+        }
+    }
+}
+
+fn salted(salt: &str, answer: i64) -> u64 {
+    hash::fnv1a(format!("{}:{}", salt, answer).as_bytes())
+}
+
+fn random_salt() -> String {
+    let mut bytes = [0u8; 4];
+    if crypto::random_bytes(&mut bytes).is_err() {
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        bytes = (hash::fnv1a(now.to_string().as_bytes()) as u32).to_le_bytes();
+    }
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// The display mode and the salt of the hashes shown, set once at startup like the colours
+static DISPLAY: OnceLock<(Mode, String)> = OnceLock::new();
+
+pub fn set_display(mode: Mode, salt: &str) {
+    let _ = DISPLAY.set((mode, salt.to_string()));
+}
+
+pub fn hidden() -> bool {
+    matches!(DISPLAY.get(), Some((mode, _)) if *mode != Mode::Show)
+}
+
+// An answer as it should be shown: as it is, masked, or as a short salted hash that
+// still lets people compare their answers if they use the same salt
+pub fn answer(answer: i64) -> String {
+    match DISPLAY.get() {
+        None | Some((Mode::Show, _)) => answer.to_string(),
+        Some((Mode::Mask, _)) => "********".to_string(),
+        Some((Mode::Hash, salt)) => format!("#{:08x}", salted(salt, answer) >> 32)
+    }
+}
+
+// An expected answer as it should be shown, hashed solutions can't be shown at all
+pub fn expected(solution: &Solution) -> String {
+    match solution {
+        Solution::Answer(a) => answer(*a),
+        Solution::Hashed { .. } => "(hashed)".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashed_solutions() {
+        let plain = Solution::parse("-42").unwrap();
+        assert_eq!(plain, Solution::Answer(-42));
+        let hashed = plain.hashed();
+        assert_ne!(hashed, plain);
+        assert_eq!(Solution::parse(&hashed.to_string()), Some(hashed.clone()));
+        assert!(hashed.matches(-42));
+        assert!(!hashed.matches(42));
+        assert_eq!(hashed.hashed(), hashed);
+        assert_eq!(Solution::parse("hash:x:zz"), None);
+    }
+}
@@ -0,0 +1,190 @@
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use adventofcode2020::log;
+use crate::journal::{self, Journal, Verdict};
+use crate::spoiler::{self, Solution};
+use crate::{fetch, http, input, selected_years, usage_error, Options};
+
+// What AoC said about a submitted answer
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Verdict(Verdict),
+    // Answers were submitted too recently, with how long to wait if AoC said it
+    Wait(Option<String>),
+    // The part is solved already, or it's not unlocked yet
+    WrongLevel,
+    Unknown,
+}
+
+// Recognise the verdict in the article of the response page
+pub fn outcome(page: &str) -> Outcome {
+    if page.contains("That's the right answer") {
+        Outcome::Verdict(Verdict::Correct)
+    } else if page.contains("answer is too high") {
+        Outcome::Verdict(Verdict::TooHigh)
+    } else if page.contains("answer is too low") {
+        Outcome::Verdict(Verdict::TooLow)
+    } else if page.contains("That's not the right answer") {
+        Outcome::Verdict(Verdict::Wrong)
+    } else if page.contains("You gave an answer too recently") {
+        let wait = page.find("You have ").and_then(|i| {
+            let rest = &page[i + "You have ".len()..];
+            rest.find(" left to wait").map(|j| rest[..j].to_string())
+        });
+        Outcome::Wait(wait)
+    } else if page.contains("You don't seem to be solving the right level") {
+        Outcome::WrongLevel
+    } else {
+        Outcome::Unknown
+    }
+}
+
+pub fn submit_answer(base_url: &str, token: &str, year: u16, day: usize, part: usize, answer: i64) -> Result<Outcome, String> {
+    let url = format!("{}/{}/day/{}/answer", base_url.trim_end_matches('/'), year, day);
+    let cookie = format!("session={}", token);
+    let headers = [("Cookie", cookie.as_str()), ("Content-Type", "application/x-www-form-urlencoded")];
+    let res = http::post(&url, &headers, &format!("level={}&answer={}", part, answer))?;
+    match res.status {
+        200 => Ok(outcome(&res.body)),
+        status => Err(format!("HTTP {}", status))
+    }
+}
+
+// Set the expected answer of a part in a solutions file, one line per day. Other entries are kept as they are,
+// and the answer is hashed if the file has hashed answers already. The days missing before this one (solved out of
+// order) are added as empty lines, which the runner reads as days without known answers.
+pub fn record(path: &Path, day: usize, part: usize, answer: i64) -> io::Result<()> {
+    let content = if path.is_file() { fs::read_to_string(path)? } else { String::new() };
+    let mut lines: Vec<Vec<String>> = content.lines().map(|l| l.split_whitespace().map(String::from).collect()).collect();
+    let hashed = content.contains("hash:");
+    if lines.len() < day { lines.resize(day, Vec::new()) }
+    let line = &mut lines[day - 1];
+    // Earlier parts without a known answer can't be left out
+    if line.len() < part - 1 { return Err(io::Error::other(format!("the answers of day {} before part {} are missing", day, part))) }
+    let solution = Solution::Answer(answer);
+    let solution = if hashed { solution.hashed() } else { solution };
+    if line.len() < part { line.push(String::new()) }
+    line[part - 1] = solution.to_string();
+    let lines: Vec<String> = lines.iter().map(|l| l.join(" ")).collect();
+    fs::write(path, lines.join("\n") + "\n")
+}
+
+// Solve a part and submit the answer: submit DAY PART [ANSWER]
+pub fn submit(opts: &Options, args: &[String]) {
+    fn arg<T: std::str::FromStr>(arg: Option<&String>) -> Option<T> {
+        arg.map(|a| a.parse().unwrap_or_else(|_| usage_error("usage: submit DAY PART [ANSWER]")))
+    }
+    if args.len() < 2 || args.len() > 3 { usage_error("usage: submit DAY PART [ANSWER]") }
+    let (day, part, answer): (usize, usize, Option<i64>) = (arg(args.first()).unwrap(), arg(args.get(1)).unwrap(), arg(args.get(2)));
+    if day == 0 || part == 0 { usage_error("usage: submit DAY PART [ANSWER]") }
+    let years = selected_years(opts);
+    if years.len() > 1 { usage_error("select a single year with --year") }
+    let year = &years[0];
+    let dir = opts.input_dir(year.year);
+
+    let answer = answer.unwrap_or_else(|| {
+        let parser = year.day_parsers.get(day.wrapping_sub(1)).unwrap_or_else(|| usage_error(&format!("no such day: {}", day)));
+        let path = dir.join(format!("{:02}.txt", day));
+        let input = input::read(&path, &opts.key_file).unwrap_or_else(|e| usage_error(&format!("{}: {}", path.display(), e)));
+        // Nothing is submitted if the day panics, the panic message is printed by the default hook
+        let answer = panic::catch_unwind(AssertUnwindSafe(|| {
+            log::enter(year.year, day, 0);
+            let parsed = parser(&input);
+            log::leave();
+            let p = parsed.parts.get(part.wrapping_sub(1)).unwrap_or_else(|| usage_error(&format!("no such part: {}", part)));
+            log::enter(year.year, day, part);
+            p.solve()
+        }));
+        log::leave();
+        answer.unwrap_or_else(|_| usage_error(&format!("y{:04} d{:02} p{:02} panicked, nothing submitted", year.year, day, part)))
+    });
+
+    // Don't waste a submission (and a minute of waiting) on an answer known to be wrong
+    let journal_path = dir.join(journal::JOURNAL_FILE);
+    if let Some(warning) = Journal::load(&journal_path).check(day, part, answer) {
+        usage_error(&format!("not submitting {}: {}", spoiler::answer(answer), warning))
+    }
+
+    let token = fetch::session(&opts.session_file).unwrap_or_else(|e| usage_error(&e));
+    let outcome = submit_answer(&opts.base_url, &token, year.year, day, part, answer).unwrap_or_else(|e| usage_error(&e));
+    let prefix = format!("y{:04} d{:02} p{:02} {}", year.year, day, part, spoiler::answer(answer));
+    match outcome {
+        Outcome::Verdict(verdict) => {
+            println!("{}: {}", prefix, verdict.name());
+            let entry = journal::Entry { day, part, guess: answer, verdict };
+            if let Err(e) = Journal::append(&journal_path, &entry) {
+                eprintln!("cannot write {}: {}", journal_path.display(), e);
+            }
+            if verdict == Verdict::Correct {
+                let path = dir.join("solutions.txt");
+                if let Err(e) = record(&path, day, part, answer) {
+                    eprintln!("cannot record the answer in {}: {}", path.display(), e);
+                }
+            }
+        },
+        Outcome::Wait(Some(wait)) => println!("{}: submitted too recently, wait {}", prefix, wait),
+        Outcome::Wait(None) => println!("{}: submitted too recently", prefix),
+        Outcome::WrongLevel => println!("{}: this part is solved already or not unlocked yet", prefix),
+        Outcome::Unknown => println!("{}: cannot recognise the verdict", prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use crate::http::test_server::{self, Request};
+
+    fn aoc(req: &Request) -> (u16, String) {
+        let article = match req.body.as_str() {
+            "level=1&answer=42" => "<article><p>That's the right answer! You are one gold star closer.</p></article>",
+            "level=1&answer=100" => "<article><p>That's not the right answer; your answer is too high.</p></article>",
+            "level=1&answer=1" => "<article><p>That's not the right answer; your answer is too low.</p></article>",
+            "level=1&answer=7" => "<article><p>That's not the right answer.</p></article>",
+            "level=2&answer=7" => "<article><p>You gave an answer too recently. You have 39s left to wait.</p></article>",
+            "level=2&answer=42" => "<article><p>You don't seem to be solving the right level.</p></article>",
+            _ => return (500, String::new())
+        };
+        (200, format!("<html><body><main>{}</main></body></html>", article))
+    }
+
+    #[test]
+    fn submit_to_mock() {
+        let (url, log) = test_server::start(aoc);
+        let submit = |part, answer| submit_answer(&url, "s3cr3t", 2020, 9, part, answer);
+        assert_eq!(submit(1, 42), Ok(Outcome::Verdict(Verdict::Correct)));
+        assert_eq!(submit(1, 100), Ok(Outcome::Verdict(Verdict::TooHigh)));
+        assert_eq!(submit(1, 1), Ok(Outcome::Verdict(Verdict::TooLow)));
+        assert_eq!(submit(1, 7), Ok(Outcome::Verdict(Verdict::Wrong)));
+        assert_eq!(submit(2, 7), Ok(Outcome::Wait(Some("39s".to_string()))));
+        assert_eq!(submit(2, 42), Ok(Outcome::WrongLevel));
+        assert!(submit(2, 0).is_err());
+
+        let log = log.lock().unwrap();
+        assert_eq!(log[0].method, "POST");
+        assert_eq!(log[0].path, "/2020/day/9/answer");
+        assert_eq!(log[0].header("Cookie"), Some("session=s3cr3t"));
+        assert_eq!(log[0].header("Content-Type"), Some("application/x-www-form-urlencoded"));
+    }
+
+    #[test]
+    fn record_solutions() {
+        let path = env::temp_dir().join(format!("aoc-submit-test-{}.txt", std::process::id()));
+        fs::write(&path, "1 2\n3\n").unwrap();
+        record(&path, 2, 2, 4).unwrap();
+        record(&path, 4, 1, -5).unwrap();
+        assert!(record(&path, 5, 2, 6).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1 2\n3 4\n\n-5\n");
+        record(&path, 1, 2, 20).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1 20\n3 4\n\n-5\n");
+
+        fs::write(&path, "hash:ab:0123\n").unwrap();
+        record(&path, 1, 2, 8).unwrap();
+        let sols = crate::solutions(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(sols[0][1].matches(8));
+        assert!(matches!(sols[0][1], Solution::Hashed { .. }));
+    }
+}
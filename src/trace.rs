@@ -0,0 +1,128 @@
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+// The time spent in a span (including the spans opened inside it) and how many times it was opened, by the path of
+// the span names from the outermost one
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub path: Vec<&'static str>,
+    pub nanos: u128,
+    pub count: u64,
+}
+
+struct Trace {
+    // The names of the open spans
+    stack: Vec<&'static str>,
+    // In the order the spans were first opened, so the parents come before their children
+    records: Vec<Record>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Trace>> = const { RefCell::new(None) };
+}
+
+// An open span, closed when it is dropped
+pub struct Span {
+    start: Option<Instant>,
+}
+
+// Called by the runner before parsing or solving a part
+pub fn start() {
+    CURRENT.with(|c| *c.borrow_mut() = Some(Trace { stack: Vec::new(), records: Vec::new() }));
+}
+
+// Called by the runner when the parsing or the part is done, returns the spans recorded since the start
+pub fn finish() -> Vec<Record> {
+    CURRENT.with(|c| c.borrow_mut().take()).map_or_else(Vec::new, |t| t.records)
+}
+
+// Parts may open named spans around their phases, the span lasts until the returned guard is dropped:
+//
+//     let _span = trace::span("neighbours");
+//
+// Spans opened while another one is open are nested in it. When the runner isn't tracing, opening a span is a
+// thread local lookup only.
+pub fn span(name: &'static str) -> Span {
+    let tracing = CURRENT.with(|c| match c.borrow_mut().as_mut() {
+        Some(t) => {
+            t.stack.push(name);
+            if !t.records.iter().any(|r| r.path == t.stack) {
+                t.records.push(Record { path: t.stack.clone(), nanos: 0, count: 0 });
+            }
+            true
+        },
+        None => false
+    });
+    Span { start: if tracing { Some(Instant::now()) } else { None } }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let elapsed = match self.start {
+            Some(start) => start.elapsed(),
+            None => return
+        };
+        CURRENT.with(|c| if let Some(t) = c.borrow_mut().as_mut() {
+            let stack = &t.stack;
+            if let Some(r) = t.records.iter_mut().find(|r| &r.path == stack) {
+                r.nanos += elapsed.as_nanos();
+                r.count += 1;
+            }
+            t.stack.pop();
+        })
+    }
+}
+
+// The time spent in a span outside the spans nested in it
+pub fn self_nanos(record: &Record, records: &[Record]) -> u128 {
+    let children: u128 = records.iter()
+        .filter(|r| r.path.len() == record.path.len() + 1 && r.path.starts_with(&record.path))
+        .map(|r| r.nanos)
+        .sum();
+    record.nanos.saturating_sub(children)
+}
+
+// The spans of a run taking `total` in the folded stack format of flamegraph tools: a line per stack with the
+// frames separated by ";" and the time spent in the innermost frame itself (in ns), under the given root frames
+pub fn folded(root: &str, total: Duration, records: &[Record]) -> Vec<String> {
+    let top: u128 = records.iter().filter(|r| r.path.len() == 1).map(|r| r.nanos).sum();
+    let mut lines = vec![format!("{} {}", root, total.as_nanos().saturating_sub(top))];
+    for r in records {
+        lines.push(format!("{};{} {}", root, r.path.join(";"), self_nanos(r, records)));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn nested_spans() {
+        let _ignored = span("before the start");
+        start();
+        for _ in 0..3 {
+            let _outer = span("outer");
+            thread::sleep(Duration::from_millis(2));
+            let _inner = span("inner");
+            thread::sleep(Duration::from_millis(1));
+        }
+        {
+            let _other = span("other");
+        }
+        let records = finish();
+        assert_eq!(records.iter().map(|r| (r.path.join(";"), r.count)).collect::<Vec<_>>(),
+                   [("outer".to_string(), 3), ("outer;inner".to_string(), 3), ("other".to_string(), 1)]);
+        assert!(records[0].nanos >= 9_000_000);
+        assert!(records[1].nanos >= 3_000_000);
+        assert!(self_nanos(&records[0], &records) >= 6_000_000);
+        assert!(self_nanos(&records[0], &records) < records[0].nanos);
+
+        let total = Duration::from_nanos(records[0].nanos as u64 + records[2].nanos as u64 + 1000);
+        let lines = folded("y2020;d01;p01", total, &records);
+        assert_eq!(lines[0], "y2020;d01;p01 1000");
+        assert!(lines[2].starts_with("y2020;d01;p01;outer;inner "));
+        assert!(finish().is_empty());
+    }
+}
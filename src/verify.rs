@@ -0,0 +1,146 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+use adventofcode2020::log;
+use crate::colour::{self, c};
+use crate::{input, selected_years, solutions, spoiler, Options};
+
+// The exit codes of verification, usage errors exit with 1
+pub const WRONG: i32 = 2;
+pub const OVER_BUDGET: i32 = 3;
+
+#[derive(Debug, Default, PartialEq)]
+struct Summary {
+    passed: usize,
+    failed: usize,
+    // Days without an input or expected answers
+    missing: usize,
+    total: Duration,
+    over_budget: bool,
+}
+
+impl Summary {
+    fn exit_code(&self) -> i32 {
+        if self.failed > 0 { WRONG } else if self.over_budget { OVER_BUDGET } else { 0 }
+    }
+}
+
+fn failed(year: u16, d: usize, what: &str) {
+    println!("{}[ERR] y{:04} d{:02} {}{}", c(colour::RED), year, d, what, c(colour::RESET));
+}
+
+fn ms(t: Duration) -> String {
+    format!("{:.3} ms", t.as_secs_f64() * 1000.0)
+}
+
+// Run every selected day once without the cache, print the wrong answers, the days over their budget and a summary,
+// and return the exit code: wrong answers (and panics) first, then budget overruns
+pub fn verify(opts: &Options) -> i32 {
+    let mut summary = Summary::default();
+    for year in selected_years(opts) {
+        let dir = opts.input_dir(year.year);
+        let sols = solutions(&dir.join("solutions.txt"));
+        for (i, parser) in year.day_parsers.iter().enumerate() {
+            let d = i + 1;
+            if !opts.days.is_empty() && !opts.days.contains(&d) { continue }
+            let path = dir.join(format!("{:02}.txt", d));
+            let expected = match sols.get(i) {
+                Some(expected) if !expected.is_empty() && input::exists(&path) => expected,
+                _ => {
+                    summary.missing += 1;
+                    continue
+                }
+            };
+            let input = match input::read(&path, &opts.key_file) {
+                Ok(input) => input,
+                Err(e) => {
+                    failed(year.year, d, &format!("    cannot read {}: {}", path.display(), e));
+                    summary.failed += 1;
+                    continue
+                }
+            };
+
+            // A panic fails the day, the panic message is printed by the default hook
+            let t = Instant::now();
+            let answers = panic::catch_unwind(AssertUnwindSafe(|| {
+                log::enter(year.year, d, 0);
+                let day = parser(&input);
+                day.parts.iter().take(expected.len()).enumerate().map(|(p, part)| {
+                    log::enter(year.year, d, p + 1);
+                    part.solve()
+                }).collect::<Vec<i64>>()
+            }));
+            log::leave();
+            let t = t.elapsed();
+            summary.total += t;
+            let mut ok = true;
+            match answers {
+                // An expected answer of a part the day doesn't have fails too
+                Ok(answers) => for (p, expected) in expected.iter().enumerate() {
+                    match answers.get(p) {
+                        Some(&actual) if expected.matches(actual) => (),
+                        Some(&actual) => {
+                            failed(year.year, d, &format!("p{:02} {:>20} is wrong", p + 1, spoiler::answer(actual)));
+                            ok = false;
+                        },
+                        None => {
+                            failed(year.year, d, &format!("p{:02} {:>20}", p + 1, "no such part"));
+                            ok = false;
+                        }
+                    }
+                },
+                Err(_) => {
+                    failed(year.year, d, "    panicked");
+                    ok = false;
+                }
+            }
+            if ok { summary.passed += 1 } else { summary.failed += 1 }
+            if let Some(budget) = opts.budget_of(year.year, d).filter(|&budget| t > budget) {
+                println!("{}[SLOW] y{:04} d{:02} {} over the budget of {}{}", c(colour::YELLOW), year.year, d, ms(t), ms(budget), c(colour::RESET));
+                summary.over_budget = true;
+            }
+        }
+    }
+    let total = if summary.total > opts.budget {
+        summary.over_budget = true;
+        format!("{}{} over the budget of {}{}", c(colour::YELLOW), ms(summary.total), ms(opts.budget), c(colour::RESET))
+    } else {
+        format!("{} of {}", ms(summary.total), ms(opts.budget))
+    };
+    println!("verify: {} passed, {} failed, {} missing in {}", summary.passed, summary.failed, summary.missing, total);
+    summary.exit_code()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(Summary { passed: 15, ..Default::default() }.exit_code(), 0);
+        assert_eq!(Summary { passed: 14, missing: 1, ..Default::default() }.exit_code(), 0);
+        assert_eq!(Summary { passed: 15, over_budget: true, ..Default::default() }.exit_code(), OVER_BUDGET);
+        assert_eq!(Summary { passed: 14, failed: 1, over_budget: true, ..Default::default() }.exit_code(), WRONG);
+    }
+
+    #[test]
+    fn verify_an_input_dir() {
+        let dir = env::temp_dir().join(format!("aoc-verify-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("01.txt"), "1721\n979\n366\n299\n675\n1456").unwrap();
+        let opts = Options { input: dir.display().to_string(), years: vec![2020], days: vec![1], ..Default::default() };
+        let verify_with = |solutions: &str, opts: &Options| {
+            fs::write(dir.join("solutions.txt"), solutions).unwrap();
+            verify(opts)
+        };
+
+        assert_eq!(verify_with("514579 241861950", &opts), 0);
+        assert_eq!(verify_with("514579 241861951", &opts), WRONG);
+        // d01 has no third part
+        assert_eq!(verify_with("514579 241861950 1", &opts), WRONG);
+        let tiny = Options { budget: Duration::from_nanos(1), ..opts.clone() };
+        assert_eq!(verify_with("514579 241861950", &tiny), OVER_BUDGET);
+        assert_eq!(verify_with("514579 1", &tiny), WRONG);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
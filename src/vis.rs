@@ -0,0 +1,160 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+// Colours of the cells in a frame, unlisted cells are drawn white
+pub type Palette = &'static [(u8, [u8; 3])];
+
+// A single frame of a visualisation: a grid of characters
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Frame {
+    pub w: usize,
+    pub h: usize,
+    pub cells: Vec<u8>,
+    pub palette: Palette,
+}
+
+impl Frame {
+    pub fn new(w: usize, h: usize, fill: u8, palette: Palette) -> Frame {
+        Frame {
+            w,
+            h,
+            cells: vec![fill; w * h],
+            palette,
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, c: u8) {
+        self.cells[y * self.w + x] = c
+    }
+
+    fn colour(&self, c: u8) -> [u8; 3] {
+        match self.palette.iter().find(|(p, _)| *p == c) {
+            Option::Some((_, rgb)) => *rgb,
+            Option::None => [255, 255, 255]
+        }
+    }
+}
+
+// Parts emit the frames of their visualisation to this
+pub trait Visualizer {
+    fn frame(&mut self, frame: &Frame);
+}
+
+#[derive(Clone)]
+pub enum Target {
+    Terminal,
+    Ppm(PathBuf),
+}
+
+const FRAME_DELAY: Duration = Duration::from_millis(30);
+const PPM_SCALE: usize = 4;
+
+// Animates the frames on an ANSI terminal
+struct Terminal {
+    title: String,
+    frames: usize,
+}
+
+impl Visualizer for Terminal {
+    fn frame(&mut self, frame: &Frame) {
+        let mut out = String::new();
+        if self.frames == 0 { out.push_str("\x1b[2J") }
+        out.push_str("\x1b[H");
+        out.push_str(&format!("{} frame {}\x1b[K\n", self.title, self.frames));
+        for row in frame.cells.chunks(frame.w) {
+            let mut last = None;
+            for &c in row {
+                let rgb = frame.colour(c);
+                if last != Some(rgb) {
+                    out.push_str(&format!("\x1b[38;2;{};{};{}m", rgb[0], rgb[1], rgb[2]));
+                    last = Some(rgb);
+                }
+                out.push(c as char);
+            }
+            out.push_str("\x1b[0m\x1b[K\n");
+        }
+        out.push_str("\x1b[J");
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        stdout.write_all(out.as_bytes()).unwrap();
+        stdout.flush().unwrap();
+        self.frames += 1;
+        thread::sleep(FRAME_DELAY);
+    }
+}
+
+// Writes the frames as a numbered sequence of PPM images
+struct Ppm {
+    dir: PathBuf,
+    prefix: String,
+    frames: usize,
+}
+
+impl Visualizer for Ppm {
+    fn frame(&mut self, frame: &Frame) {
+        let (w, h) = (frame.w * PPM_SCALE, frame.h * PPM_SCALE);
+        let mut data = format!("P6\n{} {}\n255\n", w, h).into_bytes();
+        for y in 0..h {
+            for x in 0..w {
+                data.extend_from_slice(&frame.colour(frame.cells[(y / PPM_SCALE) * frame.w + x / PPM_SCALE]));
+            }
+        }
+        fs::write(self.dir.join(format!("{}_{:05}.ppm", self.prefix, self.frames)), data).unwrap();
+        self.frames += 1;
+    }
+}
+
+pub fn visualizer(target: &Target, year: u16, day: usize, part: usize) -> Box<dyn Visualizer> {
+    match target {
+        Target::Terminal => Box::new(Terminal {
+            title: format!("y{:04} d{:02} p{:02}", year, day, part),
+            frames: 0,
+        }),
+        Target::Ppm(dir) => {
+            fs::create_dir_all(dir).unwrap();
+            Box::new(Ppm {
+                dir: dir.clone(),
+                prefix: format!("y{:04}_d{:02}_p{:02}", year, day, part),
+                frames: 0,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const PALETTE: Palette = &[(b'#', [0, 0, 0]), (b'L', [0, 128, 0])];
+
+    #[test]
+    fn frame_to_ppm() {
+        let mut frame = Frame::new(3, 2, b'.', PALETTE);
+        frame.set(0, 0, b'#');
+        frame.set(2, 1, b'L');
+        assert_eq!(frame.cells, b"#....L");
+        assert_eq!((frame.colour(b'#'), frame.colour(b'L'), frame.colour(b'.')), ([0, 0, 0], [0, 128, 0], [255, 255, 255]));
+
+        let dir = env::temp_dir().join(format!("aoc-vis-test-{}", std::process::id()));
+        let mut vis = visualizer(&Target::Ppm(dir.clone()), 2020, 11, 2);
+        vis.frame(&frame);
+        vis.frame(&Frame::new(1, 1, b'#', PALETTE));
+        let ppm = fs::read(dir.join("y2020_d11_p02_00000.ppm")).unwrap();
+        let header = format!("P6\n{} {}\n255\n", 3 * PPM_SCALE, 2 * PPM_SCALE);
+        assert!(ppm.starts_with(header.as_bytes()));
+        let pixels = &ppm[header.len()..];
+        assert_eq!(pixels.len(), 3 * 2 * PPM_SCALE * PPM_SCALE * 3);
+        let pixel = |x: usize, y: usize| &pixels[(y * 3 * PPM_SCALE + x) * 3..][..3];
+        // Each cell is a square of PPM_SCALE pixels
+        assert_eq!(pixel(PPM_SCALE - 1, PPM_SCALE - 1), [0, 0, 0]);
+        assert_eq!(pixel(PPM_SCALE, 0), [255, 255, 255]);
+        assert_eq!(pixel(3 * PPM_SCALE - 1, 2 * PPM_SCALE - 1), [0, 128, 0]);
+        assert_eq!(fs::read(dir.join("y2020_d11_p02_00001.ppm")).unwrap().len(), format!("P6\n{0} {0}\n255\n", PPM_SCALE).len() + PPM_SCALE * PPM_SCALE * 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
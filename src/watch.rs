@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use crate::colour::{self, c};
+use crate::{run_year, spoiler, DayResult, Options, Year};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum State {
+    Ok(i64),
+    Err(i64),
+    NoInput,
+}
+
+impl State {
+    fn label(&self) -> String {
+        match self {
+            State::Ok(_) => format!("{}OK{}", c(colour::GREEN), c(colour::RESET)),
+            State::Err(_) => format!("{}ERR{}", c(colour::RED), c(colour::RESET)),
+            State::NoInput => "no input".to_string()
+        }
+    }
+
+    fn answer(&self) -> Option<i64> {
+        match self {
+            State::Ok(a) | State::Err(a) => Some(*a),
+            State::NoInput => None
+        }
+    }
+}
+
+// The state of each (year, day, part), part 0 stands for the whole day when its input is missing
+type States = BTreeMap<(u16, usize, usize), State>;
+
+fn update(states: &mut States, year: u16, results: &[DayResult]) {
+    for r in results {
+        states.retain(|&(y, d, _), _| y != year || d != r.day);
+        if r.parse_nanos.is_none() {
+            states.insert((year, r.day, 0), State::NoInput);
+        }
+        for p in &r.parts {
+            let state = if p.ok() { State::Ok(p.actual) } else { State::Err(p.actual) };
+            states.insert((year, r.day, p.part), state);
+        }
+    }
+}
+
+// A line for each part whose state changed
+fn changes(old: &States, new: &States) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (&(y, d, p), new_state) in new {
+        let old_state = old.get(&(y, d, p));
+        if old_state == Some(new_state) { continue }
+
+        let part = if p == 0 { "   ".to_string() } else { format!("p{:02}", p) };
+        let line = match old_state {
+            None => format!("y{:04} d{:02} {} new: {}", y, d, part, new_state.label()),
+            Some(old_state) => {
+                let mut line = format!("y{:04} d{:02} {} {} -> {}", y, d, part, old_state.label(), new_state.label());
+                if let (Some(a), Some(b)) = (old_state.answer(), new_state.answer()) {
+                    if a != b { line.push_str(&format!(" ({} -> {})", spoiler::answer(a), spoiler::answer(b))) }
+                }
+                line
+            }
+        };
+        changes.push(line);
+    }
+    for &(y, d, p) in old.keys() {
+        if !new.contains_key(&(y, d, p)) && p > 0 {
+            changes.push(format!("y{:04} d{:02} p{:02} gone", y, d, p));
+        }
+    }
+    changes
+}
+
+fn print_diff(old: &States, new: &States) {
+    let changes = changes(old, new);
+    if changes.is_empty() {
+        println!("watch: no changes since last run");
+    } else {
+        println!("watch: changes since last run:");
+        for line in changes {
+            println!("  {}", line);
+        }
+    }
+}
+
+fn snapshot(dir: &Path) -> HashMap<String, SystemTime> {
+    let mut files = HashMap::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                files.insert(entry.file_name().to_string_lossy().into_owned(), modified);
+            }
+        }
+    }
+    files
+}
+
+// Days affected by a changed file in the input directory, None means all of them
+fn affected_day(file: &str) -> Option<Option<usize>> {
+    if file == "solutions.txt" {
+        Some(None)
+    } else if (file.len() == 6 && file.ends_with(".txt")) || (file.len() == 10 && file.ends_with(".txt.enc")) {
+        file[..2].parse().ok().map(Some)
+    } else {
+        None
+    }
+}
+
+// Re-run the affected days whenever a file in the input directory of a year changes
+pub fn watch(years: &[Year], opts: &Options) -> ! {
+    let dirs: Vec<_> = years.iter().map(|y| opts.input_dir(y.year)).collect();
+    let mut files: Vec<_> = dirs.iter().map(|d| snapshot(d)).collect();
+    let mut states = States::new();
+    for (year, dir) in years.iter().zip(dirs.iter()) {
+        update(&mut states, year.year, &run_year(year, dir, opts));
+        println!("watch: waiting for changes in {}", dir.display());
+    }
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let old = states.clone();
+        let mut nanos = 0;
+        let mut rerun_days = 0;
+        for (i, year) in years.iter().enumerate() {
+            let new_files = snapshot(&dirs[i]);
+            let mut changed: Vec<String> = new_files.keys().chain(files[i].keys())
+                .filter(|f| new_files.get(*f) != files[i].get(*f))
+                .cloned()
+                .collect();
+            changed.sort();
+            changed.dedup();
+            files[i] = new_files;
+
+            let mut all = false;
+            let mut days = Vec::new();
+            changed.retain(|file| match affected_day(file) {
+                Some(None) => { all = true; true },
+                Some(Some(d)) if opts.days.is_empty() || opts.days.contains(&d) => { days.push(d); true },
+                _ => false
+            });
+            if changed.is_empty() { continue }
+
+            let mut rerun = opts.clone();
+            if !all { rerun.days = days }
+            println!("watch: {} changed in {}", changed.join(", "), dirs[i].display());
+            let results = run_year(year, &dirs[i], &rerun);
+            nanos += results.iter().map(|r| r.nanos()).sum::<u128>();
+            rerun_days += results.len();
+            update(&mut states, year.year, &results);
+        }
+
+        if rerun_days > 0 {
+            print_diff(&old, &states);
+            println!("watch: re-ran {} day(s) in {:.3} μs", rerun_days, nanos as f32 / 1000.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn affected_days() {
+        assert_eq!(affected_day("solutions.txt"), Some(None));
+        assert_eq!(affected_day("07.txt"), Some(Some(7)));
+        assert_eq!(affected_day("14.txt.enc"), Some(Some(14)));
+        for file in ["journal.txt", "7.txt", "07.txt~", "xx.txt", ".07.txt.swp"] {
+            assert_eq!(affected_day(file), None, "{}", file);
+        }
+    }
+
+    #[test]
+    fn state_changes() {
+        let old: States = [((2020, 1, 1), State::Ok(1)), ((2020, 1, 2), State::Err(2)), ((2020, 2, 1), State::Ok(3)),
+                           ((2020, 3, 0), State::NoInput)].iter().copied().collect();
+        assert!(changes(&old, &old).is_empty());
+
+        let new: States = [((2020, 1, 1), State::Ok(1)), ((2020, 1, 2), State::Ok(5)), ((2020, 3, 1), State::Err(4))]
+            .iter().copied().collect();
+        let (ok, err) = (State::Ok(0).label(), State::Err(0).label());
+        assert_eq!(changes(&old, &new), [
+            format!("y2020 d01 p02 {} -> {} ({} -> {})", err, ok, spoiler::answer(2), spoiler::answer(5)),
+            format!("y2020 d03 p01 new: {}", err),
+            "y2020 d02 p01 gone".to_string(),
+        ]);
+        let mut update = old.clone();
+        update.insert((2020, 1, 1), State::Err(1));
+        assert_eq!(changes(&old, &update), [format!("y2020 d01 p01 {} -> {}", ok, err)]);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Year;
+use crate::{Alternate, Year};
 
 mod d01;
 mod d02;
@@ -54,6 +54,35 @@ pub fn year() -> Year {
                         //   d23::parse,
                         //   d24::parse,
                         //   d25::parse,
-                         ]
+                         ],
+        generators: vec![d01::generate,
+                         d02::generate,
+                         d03::generate,
+                         d04::generate,
+                         d05::generate,
+                         d06::generate,
+                         d07::generate,
+                         d08::generate,
+                         d09::generate,
+                         d10::generate,
+                         d11::generate,
+                         d12::generate,
+                         d13::generate,
+                         d14::generate,
+                         d15::generate,
+                        //   d16::generate,
+                        //   d17::generate,
+                        //   d18::generate,
+                        //   d19::generate,
+                        //   d20::generate,
+                        //   d21::generate,
+                        //   d22::generate,
+                        //   d23::generate,
+                        //   d24::generate,
+                        //   d25::generate,
+                         ],
+        params: vec![(9, &d09::PREAMBLE)],
+        alternates: vec![Alternate { day: 8, part: 2, name: "brute", parser: d08::parse_brute_p02 },
+                         Alternate { day: 14, part: 2, name: "brute", parser: d14::parse_brute_p02 }],
     }
 }
@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 use std::rc::Rc;
-use crate::{Day, Part};
+use crate::{Day, Details, Part};
+use crate::rng::Rng;
 
 const SUM: i32 = 2020;
 type Input = Rc<Vec<i32>>;
@@ -11,6 +12,12 @@ struct Part1 {
 
 impl Part for Part1 {
     fn solve(&self) -> i64 { p01(&self.input) }
+
+    fn explain(&self) -> Details {
+        let (a, b) = p01_entries(&self.input);
+        vec![("entry1", a.to_string()),
+             ("entry2", b.to_string())]
+    }
 }
 
 struct Part2 {
@@ -19,9 +26,16 @@ struct Part2 {
 
 impl Part for Part2 {
     fn solve(&self) -> i64 { p02(&self.input) }
+
+    fn explain(&self) -> Details {
+        let (a, b, c) = p02_entries(&self.input);
+        vec![("entry1", a.to_string()),
+             ("entry2", b.to_string()),
+             ("entry3", c.to_string())]
+    }
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let mut input: Vec<i32> = s.lines().map(|x| x.parse().unwrap()).collect();
     input.sort();
 
@@ -32,6 +46,20 @@ pub fn parse(s: String) -> Day {
     }
 }
 
+// A generated expense report of n entries with a pair and a triple summing up to 2020.
+// The rest are over 1010, so they don't add up to 2020 among themselves.
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    if n < 5 { return None }
+    let sum = SUM as usize;
+    let (x, a, b) = (rng.range(1..1010), rng.range(100..600), rng.range(100..600));
+    let mut entries = vec![x, sum - x, a, b, sum - a - b];
+    while entries.len() < n {
+        entries.push(rng.range(1011..sum));
+    }
+    rng.shuffle(&mut entries);
+    Some(entries.iter().map(|e| e.to_string()).collect::<Vec<String>>().join("\n"))
+}
+
 enum FindRes {
     Exact(usize),
     Closest(usize),
@@ -57,19 +85,19 @@ fn find_with_upper_bound(input: &Input, l: usize, h: usize, val: i32) -> FindRes
     FindRes::Closest(l - 1)
 }
 
-fn find_product_by_sum(input: &Input, h: usize, sum: i32) -> Option<i64> {
+fn find_pair_by_sum(input: &Input, h: usize, sum: i32) -> Option<(i32, i32)> {
     let mut l = 0;
     let mut h = h;
 
     while l <= h {
         match find_with_upper_bound(input, l, h, sum - input[l]) {
-            FindRes::Exact(m) => return Option::Some((input[m] * input[l]) as i64),
+            FindRes::Exact(m) => return Option::Some((input[l], input[m])),
             FindRes::Closest(m) => { h = m; l += 1 },
             FindRes::None => h -= 1
         }
         if l > h { return Option::None };
         match find_with_upper_bound(input, l, h, sum - input[h]) {
-            FindRes::Exact(m) => return Option::Some((input[m] * input[h]) as i64),
+            FindRes::Exact(m) => return Option::Some((input[m], input[h])),
             FindRes::Closest(m) => { l = m; h -= 1 },
             FindRes::None => h -= 1
         }
@@ -78,16 +106,38 @@ fn find_product_by_sum(input: &Input, h: usize, sum: i32) -> Option<i64> {
     Option::None
 }
 
+fn p01_entries(input: &Input) -> (i32, i32) {
+    find_pair_by_sum(input, input.len() - 1, SUM).unwrap()
+}
+
 fn p01(input: &Input) -> i64 {
-    find_product_by_sum(input, input.len() - 1, SUM).unwrap()
+    let (a, b) = p01_entries(input);
+    (a * b) as i64
 }
 
-fn p02(input: &Input) -> i64 {
+fn p02_entries(input: &Input) -> (i32, i32, i32) {
     let mut i = 2;
     loop {
-        match find_product_by_sum(input, i - 1, SUM - input[i]) {
-            Option::Some(product) => return product * (input[i] as i64),
+        match find_pair_by_sum(input, i - 1, SUM - input[i]) {
+            Option::Some((a, b)) => return (a, b, input[i]),
             None => i += 1
         }
     }
-}
\ No newline at end of file
+}
+
+fn p02(input: &Input) -> i64 {
+    let (a, b, c) = p02_entries(input);
+    (a * b) as i64 * (c as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn ex1() {
+        let d = super::parse("1721\n979\n366\n299\n675\n1456");
+        assert_eq!(d.parts[0].solve(), 514579);
+        assert_eq!(d.parts[0].explain(), vec![("entry1", "299".to_string()), ("entry2", "1721".to_string())]);
+        assert_eq!(d.parts[1].solve(), 241861950);
+        assert_eq!(d.parts[1].explain(), vec![("entry1", "366".to_string()), ("entry2", "675".to_string()), ("entry3", "979".to_string())]);
+    }
+}
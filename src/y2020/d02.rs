@@ -1,19 +1,19 @@
+use std::num::ParseIntError;
 use std::rc::Rc;
 use std::result::Result;
-use std::str::FromStr;
 use crate::{Day, Part};
+use crate::rng::Rng;
 
 #[derive(Debug, PartialEq)]
-struct PasswordPolicy {
+struct PasswordPolicy<'a> {
     min: u32,
     max: u32,
     c: char,
-    pwd: String,
+    pwd: &'a str,
 }
 
-impl FromStr for PasswordPolicy {
-    type Err = std::num::ParseIntError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+impl<'a> PasswordPolicy<'a> {
+    fn parse(s: &'a str) -> Result<Self, ParseIntError> {
         // 1-3 a: abcde
         //  ^ ^   ^
         //  | |   |
@@ -27,10 +27,10 @@ impl FromStr for PasswordPolicy {
             Result::Ok(min) => {
                 match s[(p1 + 1)..p2].parse::<u32>() {
                     Result::Ok(max) => Result::Ok(PasswordPolicy {
-                        min: min,
-                        max: max,
+                        min,
+                        max,
                         c: s[(p2 + 1)..(p2 + 2)].parse::<char>().unwrap(),
-                        pwd: s[(p2 + 4)..].to_string()
+                        pwd: &s[(p2 + 4)..]
                     }),
                     Result::Err(e) => Result::Err(e)
                 }
@@ -40,21 +40,21 @@ impl FromStr for PasswordPolicy {
     }
 }
 
-type Input = Rc<Vec<PasswordPolicy>>;
+type Input<'a> = Rc<Vec<PasswordPolicy<'a>>>;
 
-struct Part1 {
-    input: Input
+struct Part1<'a> {
+    input: Input<'a>
 }
 
-impl Part for Part1 {
+impl Part for Part1<'_> {
     fn solve(&self) -> i64 { p01(&self.input) }
 }
 
-struct Part2 {
-    input: Input
+struct Part2<'a> {
+    input: Input<'a>
 }
 
-impl Part for Part2 {
+impl Part for Part2<'_> {
     fn solve(&self) -> i64 { p02(&self.input) }
 }
 
@@ -74,8 +74,8 @@ fn p02(input: &Input) -> i64 {
 }
 
 
-pub fn parse(s: String) -> Day {
-    let input: Vec<PasswordPolicy> = s.lines().map(|s| s.parse().unwrap()).collect();
+pub fn parse(s: &str) -> Day<'_> {
+    let input: Vec<PasswordPolicy> = s.lines().map(|s| PasswordPolicy::parse(s).unwrap()).collect();
     
     let part1 = Box::new(Part1 { input: Rc::new(input) });
     let part2 = Box::new(Part2 { input: part1.input.clone() });
@@ -83,3 +83,16 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// A generated password database of n entries
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    let mut lines = Vec::new();
+    for _ in 0..n {
+        let len = rng.range(1..20);
+        let pwd: String = (0..len).map(|_| (b'a' + rng.range(0..5) as u8) as char).collect();
+        let min = rng.range(1..len + 1);
+        let max = rng.range(min..len + 1);
+        lines.push(format!("{}-{} {}: {}", min, max, (b'a' + rng.range(0..5) as u8) as char, pwd));
+    }
+    Some(lines.join("\n"))
+}
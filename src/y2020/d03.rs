@@ -2,6 +2,8 @@ use std::rc::Rc;
 use std::result::Result;
 use std::str::FromStr;
 use crate::{Day, Part};
+use crate::rng::Rng;
+use crate::vis::{Frame, Palette, Visualizer};
 
 #[derive(Debug, PartialEq)]
 struct Map {
@@ -12,7 +14,7 @@ struct Map {
 
 impl Map {
     fn has_tree(&self, x: usize, y: usize) -> bool {
-        self.trees[y * (self.w + 1) + x] == '#' as u8
+        self.trees[y * (self.w + 1) + x] == b'#'
     }
 }
 
@@ -23,8 +25,8 @@ impl FromStr for Map {
         let w = s.lines().next().unwrap().len();
 
         Result::Ok(Map {
-            w: w,
-            h: h,
+            w,
+            h,
             trees: s.bytes().collect()
         })
     }
@@ -38,6 +40,10 @@ struct Part1 {
 
 impl Part for Part1 {
     fn solve(&self) -> i64 { p01(&self.input) }
+
+    fn visualize(&self, vis: &mut dyn Visualizer) {
+        animate(&self.input, 3, 1, vis)
+    }
 }
 
 struct Part2 {
@@ -46,21 +52,58 @@ struct Part2 {
 
 impl Part for Part2 {
     fn solve(&self) -> i64 { p02(&self.input) }
+
+    fn visualize(&self, vis: &mut dyn Visualizer) {
+        for &(dx, dy) in &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)] {
+            animate(&self.input, dx, dy, vis)
+        }
+    }
+}
+
+const PALETTE: Palette = &[(b'.', [240, 240, 255]), (b'#', [0, 128, 0]), (b'O', [0, 0, 255]), (b'X', [255, 0, 0])];
+const VIEW_H: usize = 24;
+
+// Show a window of the map scrolling down with the toboggan
+fn animate(input: &Input, dx: usize, dy: usize, vis: &mut dyn Visualizer) {
+    let mut path = vec![b' '; input.w * input.h];
+    slope_track(input, dx, dy, |x, y, tree| {
+        path[y * input.w + x] = if tree { b'X' } else { b'O' };
+
+        let view_h = VIEW_H.min(input.h);
+        let top = y.saturating_sub(view_h / 2).min(input.h - view_h);
+        let mut frame = Frame::new(input.w, view_h, b'.', PALETTE);
+        for fy in 0..view_h {
+            for fx in 0..input.w {
+                let c = match path[(top + fy) * input.w + fx] {
+                    b' ' => if input.has_tree(fx, top + fy) { b'#' } else { b'.' },
+                    c => c
+                };
+                frame.set(fx, fy, c);
+            }
+        }
+        vis.frame(&frame);
+    });
 }
 
 fn slope(input: &Input, dx: usize, dy: usize) -> i64 {
+    slope_track(input, dx, dy, |_, _, _| ())
+}
+
+fn slope_track<F: FnMut(usize, usize, bool)>(input: &Input, dx: usize, dy: usize, mut track: F) -> i64 {
     let mut x = 0;
     let mut y = 0;
     let mut cnt = 0;
 
     while y < input.h {
-        if input.has_tree(x, y) { cnt += 1; }
-        x = x + dx;
-        y = y + dy;
+        let tree = input.has_tree(x, y);
+        if tree { cnt += 1; }
+        track(x, y, tree);
+        x += dx;
+        y += dy;
         if x >= input.w { x -= input.w; }
     }
 
-    return cnt
+    cnt
 }
 
 fn p01(input: &Input) -> i64 {
@@ -75,7 +118,7 @@ fn p02(input: &Input) -> i64 {
     slope(input, 1, 2) 
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let input: Map = s.parse().unwrap();
     
     let part1 = Box::new(Part1 { input: Rc::new(input) });
@@ -84,3 +127,9 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// A generated map of n rows, as wide as the real one
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    let rows: Vec<String> = (0..n).map(|_| (0..31).map(|_| if rng.range(0..4) == 0 { '#' } else { '.' }).collect()).collect();
+    Some(rows.join("\n"))
+}
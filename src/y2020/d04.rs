@@ -1,16 +1,17 @@
 use std::rc::Rc;
 use std::collections::HashMap;
 use crate::{Day, Part};
+use crate::rng::Rng;
 
 #[derive(Debug, PartialEq)]
-struct Passport {
-    fields: HashMap<String, String>
+struct Passport<'a> {
+    fields: HashMap<&'a str, &'a str>
 }
 
-impl Passport {
+impl Passport<'_> {
     fn is_valid(&self) -> bool {
         let req_fields = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
-        req_fields.iter().all(|k| self.fields.contains_key(&k.to_string()))
+        req_fields.iter().all(|k| self.fields.contains_key(*k))
     }
     
     fn is_strict_valid(&self) -> bool {
@@ -36,9 +37,9 @@ impl Passport {
                 Result::Ok(h) => {
                     let unit = &s[s.len() - 2..];
                     if unit == "in" {
-                        h >= 59 && h <= 76
+                        (59..=76).contains(&h)
                     } else {
-                        unit == "cm" && h >= 150 && h <= 193
+                        unit == "cm" && (150..=193).contains(&h)
                     }
                 },
                 _ => false
@@ -46,8 +47,8 @@ impl Passport {
         }) &&
         is_field("hcl", &|s| {
             s.len() == 7 &&
-            s.chars().next().unwrap() == '#' &&
-            s[1..].chars().all(|c| (c >= '0' && c <= '9') || (c >= 'a' && c <= 'f'))
+            s.starts_with('#') &&
+            s[1..].chars().all(|c: char| c.is_ascii_digit() || ('a'..='f').contains(&c))
         }) &&
         is_field("ecl", &|s| {
             s == "amb" ||
@@ -60,26 +61,26 @@ impl Passport {
         }) &&
         is_field("pid", &|s| {
             s.len() == 9 &&
-            s.chars().all(|c| c >= '0' && c <= '9')
+            s.chars().all(|c: char| c.is_ascii_digit())
         }) 
     }
 }
 
-type Input = Rc<Vec<Passport>>;
+type Input<'a> = Rc<Vec<Passport<'a>>>;
 
-struct Part1 {
-    input: Input
+struct Part1<'a> {
+    input: Input<'a>
 }
 
-impl Part for Part1 {
+impl Part for Part1<'_> {
     fn solve(&self) -> i64 { p01(&self.input) }
 }
 
-struct Part2 {
-    input: Input
+struct Part2<'a> {
+    input: Input<'a>
 }
 
-impl Part for Part2 {
+impl Part for Part2<'_> {
     fn solve(&self) -> i64 { p02(&self.input) }
 }
 
@@ -91,21 +92,21 @@ fn p02(input: &Input) -> i64 {
     input.iter().filter(|p| p.is_strict_valid()).count() as i64
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let mut input: Vec<Passport> = Vec::new();
-    let mut fields: HashMap<String, String> = HashMap::new();
+    let mut fields: HashMap<&str, &str> = HashMap::new();
     for line in s.lines() {
-        if line.len() == 0 {
-            input.push(Passport{fields: fields});
+        if line.is_empty() {
+            input.push(Passport{fields});
             fields = HashMap::new();
         } else {
             for token in line.split_whitespace() {
                 let i = token.find(':').unwrap();
-                fields.insert(token[..i].to_string(), token[(i + 1)..].to_string());
+                fields.insert(&token[..i], &token[(i + 1)..]);
             }
         }
     }
-    input.push(Passport{fields: fields});
+    input.push(Passport{fields});
     
     let part1 = Box::new(Part1 { input: Rc::new(input) });
     let part2 = Box::new(Part2 { input: part1.input.clone() });
@@ -113,3 +114,29 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// A generated batch of n passports, with some of the fields missing or invalid
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    let mut passports = Vec::new();
+    for _ in 0..n {
+        let mut fields = Vec::new();
+        for &field in &["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"] {
+            if rng.range(0..8) == 0 { continue }
+            let value = match field {
+                "byr" => rng.range(1900..2010).to_string(),
+                "iyr" => rng.range(2005..2025).to_string(),
+                "eyr" => rng.range(2015..2035).to_string(),
+                "hgt" if rng.range(0..2) == 0 => format!("{}cm", rng.range(140..200)),
+                "hgt" => format!("{}in", rng.range(55..80)),
+                "hcl" => format!("#{:06x}", rng.range(0..1 << 24)),
+                "ecl" => rng.pick(&["amb", "blu", "brn", "gry", "grn", "hzl", "oth", "xry"]).to_string(),
+                "pid" => format!("{:09}", rng.range(0..1_000_000_000)),
+                _ => rng.range(100..350).to_string()
+            };
+            fields.push(format!("{}:{}", field, value));
+        }
+        let lines: Vec<String> = fields.chunks(3).map(|line| line.join(" ")).collect();
+        passports.push(lines.join("\n"));
+    }
+    Some(passports.join("\n\n"))
+}
@@ -1,5 +1,6 @@
 use std::rc::Rc;
 use crate::{Day, Part};
+use crate::rng::Rng;
 
 type Input = Rc<Vec<u16>>;
 
@@ -32,7 +33,7 @@ fn p02(input: &Input) -> i64 {
     -1
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let mut input: Vec<u16> = s.lines().map(|line| {
         let mut id = 0;
         for c in line.chars() {
@@ -49,3 +50,22 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// Generated boarding passes of n adjacent seats with one missing among them. The plane has 1024 seats only.
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    if !(3..1000).contains(&n) { return None }
+    let first = rng.range(0..1024 - n);
+    let missing = first + rng.range(1..n);
+    let mut ids: Vec<usize> = (first..=first + n).filter(|&id| id != missing).collect();
+    rng.shuffle(&mut ids);
+    let passes: Vec<String> = ids.iter().map(|id| (0..10).rev().map(|bit| {
+        let one = id & (1 << bit) != 0;
+        match (bit >= 3, one) {
+            (true, true) => 'B',
+            (true, false) => 'F',
+            (false, true) => 'R',
+            (false, false) => 'L'
+        }
+    }).collect()).collect();
+    Some(passes.join("\n"))
+}
@@ -1,5 +1,6 @@
 use std::rc::Rc;
 use crate::{Day, Part};
+use crate::rng::Rng;
 
 type Person = u32;
 type Group = Vec<Person>;
@@ -30,7 +31,7 @@ fn p02(input: &Input) -> i64 {
     input.iter().map(|group| group.iter().fold(u32::MAX, |acc, person| acc & person).count_ones()).sum::<u32>() as i64
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let mut input: Plane = Vec::new();
     let mut group: Group = Vec::new();
     for line in s.lines() {
@@ -40,7 +41,7 @@ pub fn parse(s: String) -> Day {
         } else {
             let mut person = 0;
             for c in line.bytes() {
-                person += 1 << (c - ('a' as u8));
+                person += 1 << (c - b'a');
             }
             group.push(person);
         }
@@ -53,3 +54,18 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// Generated answers of n groups of people
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    let mut groups = Vec::new();
+    for _ in 0..n {
+        let mut people = Vec::new();
+        for _ in 0..rng.range(1..6) {
+            let mut answers: String = (b'a'..=b'z').filter(|_| rng.range(0..3) == 0).map(|c| c as char).collect();
+            if answers.is_empty() { answers.push((b'a' + rng.range(0..26) as u8) as char) }
+            people.push(answers);
+        }
+        groups.push(people.join("\n"));
+    }
+    Some(groups.join("\n\n"))
+}
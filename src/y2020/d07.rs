@@ -1,40 +1,44 @@
 use std::rc::Rc;
 use std::collections::HashMap;
 use crate::{Day, Part};
+use crate::rng::Rng;
+use crate::trace;
 
-type Colour = String;
-type ContainedBags = HashMap<Colour, u32>;
-type Bags = HashMap<Colour, ContainedBags>;
-type Input = Rc<Bags>;
+type Colour<'a> = &'a str;
+type ContainedBags<'a> = HashMap<Colour<'a>, u32>;
+type Bags<'a> = HashMap<Colour<'a>, ContainedBags<'a>>;
+type Input<'a> = Rc<Bags<'a>>;
 
-struct Part1 {
-    input: Input
+struct Part1<'a> {
+    input: Input<'a>
 }
 
-impl Part for Part1 {
+impl Part for Part1<'_> {
     fn solve(&self) -> i64 { p01(&self.input) }
 }
 
-struct Part2 {
-    input: Input
+struct Part2<'a> {
+    input: Input<'a>
 }
 
-impl Part for Part2 {
+impl Part for Part2<'_> {
     fn solve(&self) -> i64 { p02(&self.input) }
 }
 
 fn p01(input: &Input) -> i64 {
-    let my_colour = "shiny gold".to_string();
-    let mut containers: HashMap<&Colour, Vec<&Colour>> = HashMap::new();
-    let mut to_search: Vec<&Colour> = vec![&my_colour];
+    let mut containers: HashMap<Colour, Vec<Colour>> = HashMap::new();
+    let mut to_search: Vec<Colour> = vec!["shiny gold"];
     let mut cnt = 0;
     
     // Build inverse lookup table
-    for (container, contained_bags) in input.iter() {
-        for contained in contained_bags.keys() {
-            match containers.get_mut(contained) {
-                Option::Some(v) => v.push(container),
-                Option::None => { containers.insert(contained, vec![container]); }
+    {
+        let _span = trace::span("invert");
+        for (container, contained_bags) in input.iter() {
+            for contained in contained_bags.keys() {
+                match containers.get_mut(contained) {
+                    Option::Some(v) => v.push(container),
+                    Option::None => { containers.insert(contained, vec![container]); }
+                }
             }
         }
     }
@@ -43,6 +47,7 @@ fn p01(input: &Input) -> i64 {
     // - none: a new possible outermost colour without any possible enclosing containers
     // - nonempty list: a new possible outermost colour with new enclosing containers to check
     // - empty list: an already visited outermost colour
+    let _span = trace::span("search");
     loop {
         match to_search.pop() {
             Option::None => return (cnt - 1) as i64,
@@ -62,11 +67,11 @@ fn p01(input: &Input) -> i64 {
 }
 
 fn p02(input: &Input) -> i64 {
-    fn contained_bags<'a>(colour: &'a Colour, input: &'a Input, cache: &mut HashMap<&'a Colour, u32>) -> u32 {
+    fn contained_bags<'a>(colour: Colour<'a>, input: &Input<'a>, cache: &mut HashMap<Colour<'a>, u32>) -> u32 {
         match cache.get(colour) {
             Option::Some(n) => *n,
             Option::None => {
-                let n = input.get(colour).unwrap().iter().map(|(c, cnt)| {
+                let n = input.get(colour).unwrap().iter().map(|(&c, cnt)| {
                     cnt * (contained_bags(c, input, cache) + 1)
                 }).sum();
                 cache.insert(colour, n);
@@ -75,12 +80,11 @@ fn p02(input: &Input) -> i64 {
         }
     }
 
-    let my_colour = "shiny gold".to_string();
-    let mut cache: HashMap<&Colour, u32> = HashMap::new();
-    contained_bags(&my_colour, input, &mut cache) as i64
+    let mut cache: HashMap<Colour, u32> = HashMap::new();
+    contained_bags("shiny gold", input, &mut cache) as i64
 }
 
-fn parse_contained_bags(line: &str) -> ContainedBags {
+fn parse_contained_bags(line: &str) -> ContainedBags<'_> {
     let mut bags: ContainedBags = HashMap::new();
 
     // (<N> " " <B> " bag" ("s" | "") ", ")* <N> " " <B> " bag" ("s" | "") "."
@@ -90,7 +94,7 @@ fn parse_contained_bags(line: &str) -> ContainedBags {
         let s = &s[p + 1..];
 
         let p = s.find(" bag").unwrap();
-        let colour = s[..p].to_string();
+        let colour = &s[..p];
 
         bags.insert(colour, n);
     }
@@ -98,13 +102,13 @@ fn parse_contained_bags(line: &str) -> ContainedBags {
     bags
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let mut input: Bags = HashMap::new();
     for line in s.lines() {
         // <B> " bags contain " ( "no other bags." | <parse_contained_bags> )
         let pat_bags_contain = " bags contain ";
         let p = line.find(pat_bags_contain).unwrap();
-        let key = line[..p].to_string();
+        let key = &line[..p];
 
         let line = &line[p + pat_bags_contain.len()..];
         if line == "no other bags." {
@@ -120,3 +124,26 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// Generated rules of n bags with shiny gold in the middle. The bags before it may contain any of the later bags,
+// but the bags after it form a tree, so the number of bags inside shiny gold stays linear.
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    if n < 2 { return None }
+    let gold = n / 2;
+    let name = |i: usize| if i == gold { "shiny gold".to_string() } else { format!("{} {}", ["light", "dark", "muted", "dotted"][i % 4], i) };
+    let mut contents: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n];
+    for (i, bags) in contents.iter_mut().enumerate().take(gold) {
+        for _ in 0..rng.range(1..4) {
+            bags.push((rng.range(i + 1..n), rng.range(1..5)));
+        }
+    }
+    for i in gold + 1..n {
+        contents[rng.range(gold..i)].push((i, 1));
+    }
+    let rules: Vec<String> = contents.iter().enumerate().map(|(i, bags)| {
+        let bags: Vec<String> = bags.iter().map(|&(j, cnt)| format!("{} {} bag{}", cnt, name(j), if cnt == 1 { "" } else { "s" })).collect();
+        let bags = if bags.is_empty() { "no other bags".to_string() } else { bags.join(", ") };
+        format!("{} bags contain {}.", name(i), bags)
+    }).collect();
+    Some(rules.join("\n"))
+}
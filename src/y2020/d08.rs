@@ -1,6 +1,7 @@
 use std::rc::Rc;
 use std::str::FromStr;
-use crate::{Day, Part};
+use crate::{Day, Details, Part};
+use crate::rng::Rng;
 
 enum Op {
     Nop(i64),
@@ -42,6 +43,19 @@ struct Part2 {
 
 impl Part for Part2 {
     fn solve(&self) -> i64 { p02(&self.input) }
+
+    fn explain(&self) -> Details {
+        let (acc, ip) = repair(&self.input);
+        let (original, replacement) = match self.input[ip] {
+            Op::Jmp(n) => (format!("jmp {:+}", n), format!("nop {:+}", n)),
+            Op::Nop(n) => (format!("nop {:+}", n), format!("jmp {:+}", n)),
+            Op::Acc(n) => (format!("acc {:+}", n), format!("acc {:+}", n)) // cannot happen
+        };
+        vec![("flipped index", ip.to_string()),
+             ("original", original),
+             ("replacement", replacement),
+             ("acc", acc.to_string())]
+    }
 }
 
 fn p01(input: &Input) -> i64 {
@@ -61,8 +75,9 @@ fn p01(input: &Input) -> i64 {
     acc
 }
 
-fn p02(input: &Input) -> i64 {
-    fn exec_branch(acc: i64, ip: i64, input: &Input, loop_check: &mut Vec<u16>, loop_cnt: u16) -> Option<i64> {
+// Returns the accumulator and the index of the flipped instruction
+fn repair(input: &Input) -> (i64, usize) {
+    fn exec_branch(acc: i64, ip: i64, input: &Input, loop_check: &mut [u16], loop_cnt: u16) -> Option<i64> {
         if ip < 0 || ip > input.len() as i64 + 1 { return Option::None }
 
         let mut acc = acc;
@@ -93,14 +108,14 @@ fn p02(input: &Input) -> i64 {
             Op::Nop(n) => {
                 loop_cnt += 1;
                 match exec_branch(acc, ip as i64 + n, input, &mut loop_check, loop_cnt) {
-                    Option::Some(res) => return res,
+                    Option::Some(res) => return (res, ip),
                     Option::None => ip += 1
                 }
             },
             Op::Jmp(n) => {
                 loop_cnt += 1;
                 match exec_branch(acc, ip as i64 + 1, input, &mut loop_check, loop_cnt) {
-                    Option::Some(res) => return res,
+                    Option::Some(res) => return (res, ip),
                     Option::None => ip = (ip as i64 + n) as usize
                 }
             }
@@ -108,7 +123,47 @@ fn p02(input: &Input) -> i64 {
     }
 }
 
-pub fn parse(s: String) -> Day {
+fn p02(input: &Input) -> i64 {
+    repair(input).0
+}
+
+// Part 2 flipping every jmp and nop in turn and running the program, instead of only trying the branches from the
+// path the original program takes
+struct BrutePart2 {
+    input: Program
+}
+
+impl Part for BrutePart2 {
+    fn solve(&self) -> i64 { brute_p02(&self.input) }
+}
+
+fn brute_p02(input: &Program) -> i64 {
+    // The accumulator if the program with the instruction at flip swapped terminates
+    let run = |flip: usize| {
+        let mut acc = 0i64;
+        let mut ip = 0i64;
+        let mut visited = vec![false; input.len()];
+        while ip >= 0 && (ip as usize) < input.len() && !visited[ip as usize] {
+            visited[ip as usize] = true;
+            match (&input[ip as usize], ip as usize == flip) {
+                (Op::Acc(n), _) => { acc += n; ip += 1 },
+                (Op::Nop(_), false) | (Op::Jmp(_), true) => ip += 1,
+                (Op::Jmp(n), false) | (Op::Nop(n), true) => ip += n
+            }
+        }
+        if ip == input.len() as i64 { Some(acc) } else { None }
+    };
+    (0..input.len())
+        .filter(|&i| !matches!(input[i], Op::Acc(_)))
+        .find_map(run)
+        .expect("no instruction to flip")
+}
+
+pub fn parse_brute_p02(s: &str) -> Box<dyn Part + '_> {
+    Box::new(BrutePart2 { input: s.lines().map(|l| l.parse().unwrap()).collect() })
+}
+
+pub fn parse(s: &str) -> Day<'_> {
     let input: Program = s.lines().map(|l| l.parse().unwrap() ).collect();
 
     let part1 = Box::new(Part1 { input: Rc::new(input) });
@@ -117,3 +172,45 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// A generated boot code of n instructions that loops because of its last jump back to the start:
+// the forward jumps don't help when they are patched, so only patching the last one does
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    if n < 2 { return None }
+    let mut code = Vec::new();
+    while code.len() < n - 1 {
+        let i = code.len();
+        code.push(match rng.range(0..4) {
+            0 => "nop +0".to_string(),
+            1 if i + 3 < n => format!("jmp +{}", rng.range(1..4)),
+            _ => format!("acc {:+}", rng.range(0..100) as i64 - 50)
+        });
+    }
+    code.push(format!("jmp -{}", n - 1));
+    Some(code.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ex1() {
+        let d = parse("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6");
+        assert_eq!(d.parts[0].solve(), 5);
+        assert_eq!(d.parts[1].solve(), 8);
+        assert_eq!(d.parts[1].explain(), vec![("flipped index", "7".to_string()), ("original", "jmp -4".to_string()),
+                                              ("replacement", "nop -4".to_string()), ("acc", "8".to_string())]);
+    }
+
+    #[test]
+    fn brute_force_agrees() {
+        let example = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+        assert_eq!(parse_brute_p02(example).solve(), 8);
+        let mut rng = Rng::new(8);
+        for n in [10, 100, 1000] {
+            let input = generate(n, &mut rng).unwrap();
+            assert_eq!(parse_brute_p02(&input).solve(), parse(&input).parts[1].solve(), "n = {}", n);
+        }
+    }
+}
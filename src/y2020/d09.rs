@@ -1,6 +1,11 @@
 use std::rc::Rc;
 use std::cmp::Ordering;
-use crate::{Day, Part};
+use crate::{Day, Details, Part};
+use crate::params::Param;
+use crate::rng::Rng;
+
+// The number of numbers before each one its pair has to be among, 5 in the example
+pub static PREAMBLE: Param = Param { name: "preamble", default: 25, min: 2 };
 
 type Input = Rc<Vec<i64>>;
 
@@ -10,6 +15,12 @@ struct Part1 {
 
 impl Part for Part1 {
     fn solve(&self) -> i64 { p01(&self.input) }
+
+    fn explain(&self) -> Details {
+        let i = find_invalid(&self.input);
+        vec![("invalid number", self.input[i].to_string()),
+             ("index", i.to_string())]
+    }
 }
 
 struct Part2 {
@@ -18,40 +29,52 @@ struct Part2 {
 
 impl Part for Part2 {
     fn solve(&self) -> i64 { p02(&self.input) }
+
+    fn explain(&self) -> Details {
+        let (lo, hi) = find_range(&self.input);
+        let r = &self.input[lo..hi];
+        vec![("invalid number", p01(&self.input).to_string()),
+             ("range", format!("{}..{}", lo, hi)),
+             ("min", r.iter().min().unwrap().to_string()),
+             ("max", r.iter().max().unwrap().to_string())]
+    }
 }
 
-fn p01(input: &Input) -> i64 {
+// Returns the index of the first number that isn't a sum of two numbers in its preamble
+fn find_invalid(input: &Input) -> usize {
     let mut lo = 0usize;
-    let mut hi = 25usize;
+    let mut hi = PREAMBLE.get();
 
     loop {
         let preamble = &input[lo..hi];
-        match preamble.into_iter().filter(|x| {
+        match preamble.iter().find(|x| {
             let x = **x;
             let y = input[hi] - x;
             y != x && preamble.contains(&y)                   
-        }).next() {
+        }) {
             Option::Some(_) =>  {
                 lo += 1;
                 hi += 1;
             },
-            Option::None => return input[hi]
+            Option::None => return hi
         }
     }
 }
 
-fn p02(input: &Input) -> i64 {
+fn p01(input: &Input) -> i64 {
+    input[find_invalid(input)]
+}
+
+// Returns the bounds of the contiguous range summing up to the invalid number
+fn find_range(input: &Input) -> (usize, usize) {
     let target = p01(input);
     let mut lo = 0usize;
-    let mut hi = 25usize;
+    let mut hi = PREAMBLE.get();
     let mut sum: i64  = input[lo..hi].iter().sum();
 
     loop {
         match sum.cmp(&target) {
-            Ordering::Equal => {
-                let r = &input[lo..hi];
-                return r.iter().min().unwrap() + r.iter().max().unwrap();
-            },
+            Ordering::Equal => return (lo, hi),
             Ordering::Less => {
                 sum += input[hi];
                 hi += 1;
@@ -68,7 +91,13 @@ fn p02(input: &Input) -> i64 {
     }
 }
 
-pub fn parse(s: String) -> Day {
+fn p02(input: &Input) -> i64 {
+    let (lo, hi) = find_range(input);
+    let r = &input[lo..hi];
+    r.iter().min().unwrap() + r.iter().max().unwrap()
+}
+
+pub fn parse(s: &str) -> Day<'_> {
     let input: Vec<i64> = s.lines().map(|l| l.parse().unwrap() ).collect();
 
     let part1 = Box::new(Part1 { input: Rc::new(input) });
@@ -77,3 +106,44 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// A generated stream of a preamble of k numbers and n more: each is the sum of two of the k before it, except for the
+// last one, which is the sum of a few numbers right after the preamble. The preamble is spread over a large range, so
+// there are few other pairs with the same sum, and the parts scan as much of the window as in a real input. The numbers
+// grow exponentially, so there can be a few hundred of them with the default preamble, and more with longer ones.
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    let k = PREAMBLE.get();
+    if n < 4 { return None }
+    let mut numbers: Vec<i64> = Vec::new();
+    while numbers.len() < k {
+        let x = rng.range(1..1 << 20) as i64;
+        if !numbers.contains(&x) { numbers.push(x) }
+    }
+    while numbers.len() < k + n - 1 {
+        let window = &numbers[numbers.len() - k..];
+        let (a, b) = (*rng.pick(window), *rng.pick(window));
+        if a != b { numbers.push(a.checked_add(b)?) }
+    }
+    let window = &numbers[numbers.len() - k..];
+    let invalid = (k + 2..numbers.len()).map(|j| numbers[k..j].iter().sum::<i64>())
+        .find(|&sum| !window.iter().any(|&x| sum - x != x && window.contains(&(sum - x))))?;
+    numbers.push(invalid);
+    Some(numbers.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PREAMBLE;
+
+    #[test]
+    fn ex1() {
+        PREAMBLE.set(5).unwrap();
+        let d = super::parse("35\n20\n15\n25\n47\n40\n62\n55\n65\n95\n102\n117\n150\n182\n127\n219\n299\n277\n309\n576");
+        assert_eq!(d.parts[0].solve(), 127);
+        assert_eq!(d.parts[0].explain(), vec![("invalid number", "127".to_string()), ("index", "14".to_string())]);
+        assert_eq!(d.parts[1].solve(), 62);
+        assert_eq!(d.parts[1].explain(), vec![("invalid number", "127".to_string()), ("range", "2..6".to_string()),
+                                              ("min", "15".to_string()), ("max", "47".to_string())]);
+        PREAMBLE.reset();
+    }
+}
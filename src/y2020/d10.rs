@@ -1,5 +1,6 @@
 use std::rc::Rc;
 use crate::{Day, Part};
+use crate::rng::Rng;
 
 type Input = Rc<Vec<usize>>;
 
@@ -28,7 +29,7 @@ fn p01(input: &Input) -> i64 {
         last = *jolt;
     }
 
-    return (deltas[1] * deltas[3]) as i64
+    (deltas[1] * deltas[3]) as i64
 }
 
 fn p02(input: &Input) -> i64 {
@@ -53,7 +54,7 @@ fn p02(input: &Input) -> i64 {
     options[3]
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let mut input: Vec<usize> = s.lines().map(|l| l.parse().unwrap() ).collect();
     input.sort();
 
@@ -63,3 +64,18 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// A generated bag of n adapters. They differ by 3 jolts, or by 1 but never twice in a row: there's a single
+// arrangement of them, so the answer of part 2 doesn't overflow.
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    let mut adapters = Vec::new();
+    let mut jolts = 0;
+    let mut step = 3;
+    for _ in 0..n {
+        step = if step == 3 && rng.range(0..2) == 0 { 1 } else { 3 };
+        jolts += step;
+        adapters.push(jolts);
+    }
+    rng.shuffle(&mut adapters);
+    Some(adapters.iter().map(|a| a.to_string()).collect::<Vec<String>>().join("\n"))
+}
@@ -1,10 +1,16 @@
 use std::rc::Rc;
 use std::str::FromStr;
 use crate::{Day, Part};
+use crate::rng::Rng;
+use crate::progress;
+use crate::trace;
+use crate::vis::{Frame, Palette, Visualizer};
 
 // The 2D seat map represented as a 1D, line continuous vector with extra padding lines and rows around the actual area
 type Seats = Vec<u8>;
 
+const PALETTE: Palette = &[(b'.', [64, 64, 64]), (b'L', [0, 192, 0]), (b'#', [224, 0, 0])];
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct WaitingArea {
     initial: Seats,
@@ -20,26 +26,59 @@ impl WaitingArea {
         self.initial.to_vec()
     }
 
+    // Strip the padding around the seats for visualisation
+    fn frame(&self, seats: &Seats) -> Frame {
+        let mut frame = Frame::new(self.w, self.h, b'.', PALETTE);
+        for y in 0..self.h {
+            let i = self.top_left + y * self.vstep;
+            frame.cells[y * self.w..(y + 1) * self.w].copy_from_slice(&seats[i..i + self.w]);
+        }
+        frame
+    }
+
+    // Build a lookup map of all the first visible seats in each direction
+    fn neighbours(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut neighbours: Vec<(usize, Vec<usize>)> = Vec::new();
+        let mut i = self.top_left;
+        while i <= self.bottom_right {
+            if self.initial[i] == b'L' || self.initial[i] == b'#' {
+                let mut directions = Vec::new();
+                for d in &[1, self.vstep + 1, self.vstep - 1, self.vstep] {
+                    let mut j = i + d;
+                    while self.initial[j] == b'.' { j += d }
+                    if self.initial[j] != b'X' { directions.push(j) }
+
+                    j = i - d;
+                    while self.initial[j] == b'.' { j -= d }
+                    if self.initial[j] != b'X' { directions.push(j) }
+                }
+                neighbours.push((i, directions))
+            }
+            i += 1
+        }
+        neighbours
+    }
+
     fn step(&self, from: &Seats, to: &mut Seats) {
         let mut i = self.top_left;
         while i <= self.bottom_right {
-            if from[i] == 'L' as u8 {
+            if from[i] == b'L' {
                 if [i - self.vstep - 1, i - self.vstep, i - self.vstep + 1,
                     i - 1,                              i + 1,
                     i + self.vstep - 1, i + self.vstep, i + self.vstep + 1
-                ].iter().any(|&j| from[j] == '#' as u8) {
-                    to[i] = 'L' as u8
+                ].iter().any(|&j| from[j] == b'#') {
+                    to[i] = b'L'
                 } else {
-                    to[i] = '#' as u8
+                    to[i] = b'#'
                 }
-            } else if from[i] == '#' as u8 {
+            } else if from[i] == b'#' {
                 if [i - self.vstep - 1, i - self.vstep, i - self.vstep + 1,
                     i - 1,                              i + 1,
                     i + self.vstep - 1, i + self.vstep, i + self.vstep + 1
-                ].iter().filter(|&&j| from[j] == '#' as u8).count() >= 4 {
-                    to[i] = 'L' as u8
+                ].iter().filter(|&&j| from[j] == b'#').count() >= 4 {
+                    to[i] = b'L'
                 } else {
-                    to[i] = '#' as u8
+                    to[i] = b'#'
                 }
             }
             i += 1
@@ -48,17 +87,17 @@ impl WaitingArea {
 
     fn step2(&self, from: &Seats, to: &mut Seats, neighbours: &Vec<(usize, Vec<usize>)>) {
         for (i, js) in neighbours {
-            if from[*i] == 'L' as u8 {
-                if js.iter().any(|&j| from[j] == '#' as u8) {
-                    to[*i] = 'L' as u8
+            if from[*i] == b'L' {
+                if js.iter().any(|&j| from[j] == b'#') {
+                    to[*i] = b'L'
                 } else {
-                    to[*i] = '#' as u8
+                    to[*i] = b'#'
                 }
-            } else if from[*i] == '#' as u8 {
-                if js.iter().filter(|&&j| from[j] == '#' as u8).count() >= 5 {
-                    to[*i] = 'L' as u8
+            } else if from[*i] == b'#' {
+                if js.iter().filter(|&&j| from[j] == b'#').count() >= 5 {
+                    to[*i] = b'L'
                 } else {
-                    to[*i] = '#' as u8
+                    to[*i] = b'#'
                 }
             }
         }
@@ -74,7 +113,7 @@ impl FromStr for WaitingArea {
         let top_left = vstep + 1;
         let bottom_right = w + 1 + vstep * h;
 
-        let mut initial = vec!['X' as u8; (w + 2) * (h + 2)];
+        let mut initial = vec![b'X'; (w + 2) * (h + 2)];
         let mut i = top_left;
         for line in s.lines() {
             initial[i..i+w].as_mut().copy_from_slice(line.as_bytes());
@@ -82,12 +121,12 @@ impl FromStr for WaitingArea {
         }
 
         Result::Ok(WaitingArea{
-            initial: initial,
-            w: w,
-            h: h,
-            vstep: vstep,
-            top_left: top_left,
-            bottom_right: bottom_right,
+            initial,
+            w,
+            h,
+            vstep,
+            top_left,
+            bottom_right,
         })
     }
 }
@@ -100,6 +139,11 @@ struct Part1 {
 
 impl Part for Part1 {
     fn solve(&self) -> i64 { p01(&self.input) }
+
+    fn visualize(&self, vis: &mut dyn Visualizer) {
+        let input = &self.input;
+        animate(input, vis, |from, to| input.step(from, to))
+    }
 }
 
 struct Part2 {
@@ -108,53 +152,64 @@ struct Part2 {
 
 impl Part for Part2 {
     fn solve(&self) -> i64 { p02(&self.input) }
+
+    fn visualize(&self, vis: &mut dyn Visualizer) {
+        let input = &self.input;
+        let neighbours = input.neighbours();
+        animate(input, vis, |from, to| input.step2(from, to, &neighbours))
+    }
+}
+
+// Run a simulation until it stabilises, emitting every state as a frame
+fn animate<F: Fn(&Seats, &mut Seats)>(input: &Input, vis: &mut dyn Visualizer, step: F) {
+    let mut b1 = input.crate_seats();
+    let mut b2 = input.crate_seats();
+    vis.frame(&input.frame(&b1));
+    loop {
+        step(&b1, &mut b2);
+        if b1 == b2 { return }
+        vis.frame(&input.frame(&b2));
+        std::mem::swap(&mut b1, &mut b2);
+    }
 }
 
 fn p01(input: &Input) -> i64 {
+    let _span = trace::span("simulate");
     let mut b1 = input.crate_seats();
     let mut b2 = input.crate_seats();
+    let mut steps = 0;
     loop {
+        steps += 2;
+        progress::report(steps, None);
         input.step(&b1, &mut b2);
-        if b1 == b2 { return b2.iter().filter(|&&c| c == '#' as u8).count() as i64 }
+        if b1 == b2 { return b2.iter().filter(|&&c| c == b'#').count() as i64 }
         input.step(&b2, &mut b1);
-        if b1 == b2 { return b1.iter().filter(|&&c| c == '#' as u8).count() as i64 }
+        if b1 == b2 { return b1.iter().filter(|&&c| c == b'#').count() as i64 }
     }
 }
 
 fn p02(input: &Input) -> i64 {
-    let mut neighbours: Vec<(usize, Vec<usize>)> = Vec::new();
-
-    // Build a lookup map of all neighbours
-    let mut i = input.top_left;
-    while i <= input.bottom_right {
-        if input.initial[i] == 'L' as u8 || input.initial[i] == '#' as u8 {
-            let mut directions = Vec::new();
-            for d in &[1, input.vstep + 1, input.vstep - 1, input.vstep] {
-                let mut j = i + d;
-                while input.initial[j] == '.' as u8 { j += d }
-                if input.initial[j] != 'X' as u8 { directions.push(j) }
-
-                j = i - d;
-                while input.initial[j] == '.' as u8 { j -= d }
-                if input.initial[j] != 'X' as u8 { directions.push(j) }
-            }
-            neighbours.push((i, directions))
-        }
-        i += 1
-    }
+    let neighbours = {
+        let _span = trace::span("neighbours");
+        input.neighbours()
+    };
 
     // Run the simulation
+    let _span = trace::span("simulate");
     let mut b1 = input.crate_seats();
     let mut b2 = input.crate_seats();
+    let mut steps = 0;
     loop {
+        steps += 2;
+        progress::report(steps, None);
         input.step2(&b1, &mut b2, &neighbours);
-        if b1 == b2 { return b2.iter().filter(|&&c| c == '#' as u8).count() as i64 }
+        if b1 == b2 { return b2.iter().filter(|&&c| c == b'#').count() as i64 }
         input.step2(&b2, &mut b1, &neighbours);
-        if b1 == b2 { return b1.iter().filter(|&&c| c == '#' as u8).count() as i64 }
+        if b1 == b2 { return b1.iter().filter(|&&c| c == b'#').count() as i64 }
     }
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let input: WaitingArea = s.parse().unwrap();
 
     let part1 = Box::new(Part1 { input: Rc::new(input) });
@@ -163,3 +218,34 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// The seats still changing after a number of rounds of a simulation, or None if it settles by then
+fn unsettled<F: Fn(&Seats, &mut Seats)>(input: &WaitingArea, step: F) -> Option<Vec<usize>> {
+    let mut b1 = input.crate_seats();
+    let mut b2 = input.crate_seats();
+    for _ in 0..200 {
+        step(&b1, &mut b2);
+        if b1 == b2 { return None }
+        std::mem::swap(&mut b1, &mut b2);
+    }
+    Some((0..b1.len()).filter(|&i| b1[i] != b2[i]).collect())
+}
+
+// A generated waiting area of n rows, as wide as the real one. Some seats of a random area may keep flipping forever,
+// while the real areas settle: those seats are turned into floor until both simulations settle.
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    let mut rows: Vec<Vec<u8>> = (0..n).map(|_| (0..90).map(|_| if rng.range(0..4) == 0 { b'.' } else { b'L' }).collect()).collect();
+    loop {
+        let s = rows.iter().map(|row| String::from_utf8_lossy(row)).collect::<Vec<_>>().join("\n");
+        let area: WaitingArea = s.parse().unwrap();
+        let neighbours = area.neighbours();
+        let unsettled = unsettled(&area, |from, to| area.step(from, to))
+            .or_else(|| unsettled(&area, |from, to| area.step2(from, to, &neighbours)));
+        match unsettled {
+            None => return Some(s),
+            Some(seats) => for i in seats {
+                rows[i / area.vstep - 1][i % area.vstep - 1] = b'.'
+            }
+        }
+    }
+}
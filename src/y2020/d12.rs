@@ -1,6 +1,8 @@
 use std::rc::Rc;
 use std::str::FromStr;
 use crate::{Day, Part};
+use crate::rng::Rng;
+use crate::vis::{Frame, Palette, Visualizer};
 
 #[derive(Eq, PartialEq, Debug, Clone)]
 enum Action {
@@ -38,6 +40,12 @@ struct Part1 {
 
 impl Part for Part1 {
     fn solve(&self) -> i64 { p01(&self.input) }
+
+    fn visualize(&self, vis: &mut dyn Visualizer) {
+        let mut track = vec![(0, 0, None)];
+        p01_track(&self.input, |x, y| track.push((x, y, None)));
+        animate(&track, vis)
+    }
 }
 
 struct Part2 {
@@ -46,9 +54,52 @@ struct Part2 {
 
 impl Part for Part2 {
     fn solve(&self) -> i64 { p02(&self.input) }
+
+    fn visualize(&self, vis: &mut dyn Visualizer) {
+        let mut track = vec![(0, 0, Some((10, -1)))];
+        p02_track(&self.input, |x, y, wx, wy| track.push((x, y, Some((x + wx, y + wy)))));
+        animate(&track, vis)
+    }
+}
+
+// Positions of the ship and the waypoint (if any) after each instruction
+type Track = Vec<(i32, i32, Option<(i32, i32)>)>;
+
+const PALETTE: Palette = &[(b' ', [0, 0, 32]), (b'.', [96, 96, 160]), (b'@', [255, 255, 0]), (b'w', [255, 64, 64])];
+const MAX_W: i32 = 120;
+const MAX_H: i32 = 60;
+
+fn animate(track: &Track, vis: &mut dyn Visualizer) {
+    let points = || track.iter().flat_map(|&(x, y, w)| std::iter::once((x, y)).chain(w));
+    let min_x = points().map(|p| p.0).min().unwrap();
+    let max_x = points().map(|p| p.0).max().unwrap();
+    let min_y = points().map(|p| p.1).min().unwrap();
+    let max_y = points().map(|p| p.1).max().unwrap();
+
+    // Scale the sea down to fit on the screen
+    let scale = ((max_x - min_x) / MAX_W).max((max_y - min_y) / MAX_H) + 1;
+    let cell = |x: i32, y: i32| (((x - min_x) / scale) as usize, ((y - min_y) / scale) as usize);
+    let (w, h) = cell(max_x, max_y);
+
+    let mut trail = Frame::new(w + 1, h + 1, b' ', PALETTE);
+    for &(x, y, waypoint) in track {
+        let (cx, cy) = cell(x, y);
+        let mut frame = trail.clone();
+        if let Some((wx, wy)) = waypoint {
+            let (wx, wy) = cell(wx, wy);
+            frame.set(wx, wy, b'w');
+        }
+        frame.set(cx, cy, b'@');
+        vis.frame(&frame);
+        trail.set(cx, cy, b'.');
+    }
 }
 
 fn p01(input: &Input) -> i64 {
+    p01_track(input, |_, _| ())
+}
+
+fn p01_track<F: FnMut(i32, i32)>(input: &Input, mut track: F) -> i64 {
     let mut x = 0i32;
     let mut y = 0i32;
     let mut d = 0usize;
@@ -65,12 +116,17 @@ fn p01(input: &Input) -> i64 {
             Action::Right => d = (d + *val as usize / 90) % 4,
             _ => () // cannot happen
         }
+        track(x, y);
     }
 
     (x.abs() + y.abs()) as i64
 }
 
 fn p02(input: &Input) -> i64 {
+    p02_track(input, |_, _, _, _| ())
+}
+
+fn p02_track<F: FnMut(i32, i32, i32, i32)>(input: &Input, mut track: F) -> i64 {
     let mut wx = 10;
     let mut wy = -1;
     let mut sx = 0;
@@ -94,12 +150,13 @@ fn p02(input: &Input) -> i64 {
             } else if *val == 180 { back(&mut wx, &mut wy)
             } else { left(&mut wx, &mut wy) },
         }
+        track(sx, sy, wx, wy);
     }
 
     (sx.abs() + sy.abs()) as i64
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let input = s.lines().map(|l| {
         (l[..1].parse::<Action>().unwrap(), l[1..].parse::<i32>().unwrap())
     }).collect();
@@ -110,3 +167,15 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// Generated navigation instructions, n of them. The waypoint wanders away slowly, but the ship would leave the
+// range of an i32 after a few ten thousand instructions.
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    if n > 20_000 { return None }
+    let instructions: Vec<String> = (0..n).map(|_| match rng.range(0..7) {
+        0..=3 => format!("{}{}", rng.pick(&['N', 'E', 'S', 'W']), rng.range(1..10)),
+        4 | 5 => format!("{}{}", rng.pick(&['L', 'R']), rng.pick(&[90, 180, 270])),
+        _ => format!("F{}", rng.range(1..100))
+    }).collect();
+    Some(instructions.join("\n"))
+}
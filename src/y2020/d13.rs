@@ -1,5 +1,6 @@
 use std::rc::Rc;
-use crate::{Day, Part};
+use crate::{Day, Details, Part};
+use crate::rng::Rng;
 
 #[derive(Eq, PartialEq, Debug, Clone)]
 enum BusLine {
@@ -21,6 +22,12 @@ struct Part1 {
 
 impl Part for Part1 {
     fn solve(&self) -> i64 { p01(&self.input) }
+
+    fn explain(&self) -> Details {
+        let (id, wait) = earliest_bus(&self.input);
+        vec![("bus id", id.to_string()),
+             ("wait", wait.to_string())]
+    }
 }
 
 struct Part2 {
@@ -31,26 +38,29 @@ impl Part for Part2 {
     fn solve(&self) -> i64 { p02(&self.input) }
 }
 
-fn p01(input: &Input) -> i64 {
+// Returns the id of the first bus to depart and the time to wait for it
+fn earliest_bus(input: &Input) -> (u32, u32) {
     let mut min_wait = u32::MAX;
     let mut min_wait_id = 0;
     for service in &input.services {
-        match service {
-            BusLine::Id(id) => {
-                let miss = input.t % id;
-                if miss == 0 { return 0 }
-                
-                let wait = id - miss;
-                if wait < min_wait {
-                    min_wait = wait;
-                    min_wait_id = *id;
-                }
-            },
-            _ => ()
+        if let BusLine::Id(id) = service {
+            let miss = input.t % id;
+            if miss == 0 { return (*id, 0) }
+            
+            let wait = id - miss;
+            if wait < min_wait {
+                min_wait = wait;
+                min_wait_id = *id;
+            }
         }
     }
     
-    min_wait_id as i64 * min_wait as i64
+    (min_wait_id, min_wait)
+}
+
+fn p01(input: &Input) -> i64 {
+    let (id, wait) = earliest_bus(input);
+    id as i64 * wait as i64
 }
 
 fn p02(input: &Input) -> i64 {
@@ -95,13 +105,13 @@ fn p02(input: &Input) -> i64 {
     t
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let mut lines = s.lines();
     let t = lines.next().unwrap().parse().unwrap();
     let services = lines.next().unwrap().split(',').map(|token| if token == "x" { BusLine::X } else { BusLine::Id(token.parse().unwrap()) }).collect();
     let input = Schedule {
-        t: t,
-        services: services
+        t,
+        services
     };
     
     let part1 = Box::new(Part1 { input: Rc::new(input) });
@@ -111,12 +121,27 @@ pub fn parse(s: String) -> Day {
     }
 }
 
+// A generated bus schedule with n entries. Only a few of them are buses (with coprime ids, so that part 2 has
+// a solution), the rest are out of service.
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    if n == 0 { return None }
+    let mut services = vec!["x".to_string(); n];
+    let mut positions: Vec<usize> = (1..n).collect();
+    rng.shuffle(&mut positions);
+    positions.insert(0, 0);
+    for (&i, id) in positions.iter().zip(&[13, 17, 19, 23, 29, 37, 41, 431, 601]) {
+        services[i] = id.to_string();
+    }
+    Some(format!("{}\n{}", rng.range(1_000_000..1_010_000), services.join(",")))
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
     fn ex1() {
-        let d = super::parse("939\n7,13,x,x,59,x,31,19".to_string());
+        let d = super::parse("939\n7,13,x,x,59,x,31,19");
         assert_eq!(d.parts[0].solve(), 295);
+        assert_eq!(d.parts[0].explain(), vec![("bus id", "59".to_string()), ("wait", "5".to_string())]);
         assert_eq!(d.parts[1].solve(), 1068781);
     }
 }
\ No newline at end of file
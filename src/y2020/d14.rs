@@ -1,6 +1,9 @@
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::str::FromStr;
 use crate::{Day, Part};
+use crate::rng::Rng;
+use crate::log;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 enum Content {
@@ -63,9 +66,11 @@ impl Node {
             let mut new_bits = 1;
             let mut mask = 1;
             while cmp & mask == 0 {
-                mask = mask << 1;
+                mask <<= 1;
                 new_bits += 1;
             }
+            log::trace!("splitting {:b} (floating {:b}) after {} common bits with {:b} (floating {:b})",
+                        self.addr, self.floats, new_bits - 1, addr, floats);
             
             match &mut self.content {
                 Content::Value(v) => {
@@ -121,8 +126,8 @@ impl Node {
             }
 
             let mask = mask - 1;
-            self.addr = self.addr & mask;
-            self.floats = self.floats & mask;
+            self.addr &= mask;
+            self.floats &= mask;
         }
     }
     
@@ -147,17 +152,17 @@ type Ops = Vec<Op>;
 impl FromStr for Op {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.as_bytes()[5] == '=' as u8 {
+        if s.as_bytes()[5] == b'=' {
             // Mask
             let mut and = 0u64;
             let mut or = 0u64;
             for c in s[7..].bytes() {
                 and <<= 1;
                 or <<= 1;
-                if c == '1' as u8 {
+                if c == b'1' {
                     or |= 1;
                     and |= 1;
-                } else if c == 'X' as u8 {
+                } else if c == b'X' {
                     and |= 1;
                 }
             }
@@ -239,6 +244,7 @@ fn p02(input: &Input) -> i64 {
             },
             Op::Mem(addr, value) => {
                 let addr = addr | sets;
+                log::debug!("mem[{:036b}] = {} with {} floating bits", addr, value, floats.count_ones());
                 mem.insert(addr, floats, *value)
             }
         }
@@ -247,7 +253,43 @@ fn p02(input: &Input) -> i64 {
     mem.sum() as i64
 }
 
-pub fn parse(s: String) -> Day {
+// Part 2 writing every address the floating bits stand for, instead of keeping them in a trie
+struct BrutePart2 {
+    input: Ops
+}
+
+impl Part for BrutePart2 {
+    fn solve(&self) -> i64 { brute_p02(&self.input) }
+}
+
+fn brute_p02(input: &Ops) -> i64 {
+    let mut mem: HashMap<u64, u64> = HashMap::new();
+    let (mut sets, mut floats) = (0u64, 0u64);
+    for op in input {
+        match op {
+            Op::Mask(and, or) => {
+                sets = and | or;
+                floats = and & !or;
+            },
+            Op::Mem(addr, value) => {
+                // Every subset of the floating bits, from all of them down to none
+                let mut bits = floats;
+                loop {
+                    mem.insert((addr | sets) & !floats | bits, *value);
+                    if bits == 0 { break }
+                    bits = (bits - 1) & floats;
+                }
+            }
+        }
+    }
+    mem.values().sum::<u64>() as i64
+}
+
+pub fn parse_brute_p02(s: &str) -> Box<dyn Part + '_> {
+    Box::new(BrutePart2 { input: s.lines().map(|l| l.parse().unwrap()).collect() })
+}
+
+pub fn parse(s: &str) -> Day<'_> {
     let input: Ops = s.lines().map(|l| l.parse().unwrap()).collect();
     
     let part1 = Box::new(Part1 { input: Rc::new(input) });
@@ -257,6 +299,22 @@ pub fn parse(s: String) -> Day {
     }
 }
 
+// A generated program of n lines: masks with at most 5 floating bits, each followed by a few writes
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    let mut lines = Vec::new();
+    while lines.len() < n {
+        let mut mask: Vec<u8> = (0..36).map(|_| *rng.pick(b"01")).collect();
+        for _ in 0..rng.range(0..6) {
+            mask[rng.range(0..36)] = b'X';
+        }
+        lines.push(format!("mask = {}", String::from_utf8(mask).unwrap()));
+        for _ in 0..rng.range(1..5).min(n - lines.len()) {
+            lines.push(format!("mem[{}] = {}", rng.range(0..1 << 16), rng.range(0..1 << 30)));
+        }
+    }
+    Some(lines.join("\n"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -264,23 +322,32 @@ mod tests {
     #[test]
     fn p01_test() {
         let d = parse(
-            String::from("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\n\
-                          mem[8] = 11\n\
-                          mem[7] = 101\n\
-                          mem[8] = 0\n"));
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\n\
+             mem[8] = 11\n\
+             mem[7] = 101\n\
+             mem[8] = 0\n");
         assert_eq!(d.parts[0].solve(), 165);
     }
 
     #[test]
     fn p02_test() {
         let d = parse(
-            String::from("mask = 000000000000000000000000000000X1001X\n\
-                          mem[42] = 100\n\
-                          mask = 00000000000000000000000000000000X0XX\n\
-                          mem[26] = 1\n"));
+            "mask = 000000000000000000000000000000X1001X\n\
+             mem[42] = 100\n\
+             mask = 00000000000000000000000000000000X0XX\n\
+             mem[26] = 1\n");
         assert_eq!(d.parts[1].solve(), 208);
     }
 
+    #[test]
+    fn brute_force_agrees() {
+        let mut rng = Rng::new(14);
+        for n in [10, 100, 1000] {
+            let input = generate(n, &mut rng).unwrap();
+            assert_eq!(parse_brute_p02(&input).solve(), parse(&input).parts[1].solve(), "n = {}", n);
+        }
+    }
+
     #[test]
     fn insert_to_content() {
         let mut n0 = Node {addr: 4, floats: 0, content: Content::Value(10)};
@@ -1,8 +1,13 @@
 use std::rc::Rc;
 use crate::{Day, Part};
+use crate::rng::Rng;
+use crate::progress;
 
 type Input = Rc<Vec<usize>>;
 
+// The number spoken on this turn is the answer of part 2
+const P02_TURNS: usize = 30000000;
+
 struct Part1 {
     input: Input
 }
@@ -40,7 +45,7 @@ fn p01(input: &Input) -> i64 {
 }
 
 fn p02(input: &Input) -> i64 {
-    let mut memory = vec![0usize; 30000000];
+    let mut memory = vec![0usize; P02_TURNS];
     for (i, &current) in input.iter().enumerate() {
         memory[current] = i + 1;
     }
@@ -51,7 +56,8 @@ fn p02(input: &Input) -> i64 {
         let current = if memory[last] == 0 { 0 } else { i - memory[last] };
         memory[last] = i;
         i += 1;
-        if i == 30000000 {
+        if i & 0xffff == 0 { progress::report(i as u64, Some(P02_TURNS as u64)) }
+        if i == P02_TURNS {
             return current as i64
         } else {
             last = current;
@@ -59,7 +65,7 @@ fn p02(input: &Input) -> i64 {
     }
 }
 
-pub fn parse(s: String) -> Day {
+pub fn parse(s: &str) -> Day<'_> {
     let input = s.split(',').map(|n| n.parse().unwrap()).collect();    
     let part1 = Box::new(Part1 { input: Rc::new(input) });
     let part2 = Box::new(Part2 { input: part1.input.clone() });
@@ -67,3 +73,11 @@ pub fn parse(s: String) -> Day {
         parts: vec![part1, part2]
     }
 }
+
+// Generated starting numbers: n different ones below 2020, and at most a thousand of them
+pub fn generate(n: usize, rng: &mut Rng) -> Option<String> {
+    if !(1..=1000).contains(&n) { return None }
+    let mut numbers: Vec<usize> = (0..2020).collect();
+    rng.shuffle(&mut numbers);
+    Some(numbers[..n].iter().map(|x| x.to_string()).collect::<Vec<String>>().join(","))
+}
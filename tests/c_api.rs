@@ -0,0 +1,37 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+// Build examples/c/check.c against the cdylib and the shipped header, and check the answers of every day with it
+#[test]
+fn c_example_checks_the_answers() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // The test runs from target/*/deps, the library is built next to it
+    let lib_dir = env::current_exe().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    let exe = env::temp_dir().join(format!("aoc-c-check-{}", std::process::id()));
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&cc)
+        .arg(root.join("examples/c/check.c"))
+        .arg("-I").arg(root.join("include"))
+        .arg("-L").arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .args(["-ladventofcode2020", "-Wall", "-Werror", "-o"]).arg(&exe)
+        .status();
+    match status {
+        Ok(status) => assert!(status.success(), "cannot compile the C example"),
+        Err(e) => {
+            eprintln!("skipping the C example, cannot run {}: {}", cc, e);
+            return
+        }
+    }
+
+    let out = Command::new(&exe).arg(root.join("input")).output().unwrap();
+    std::fs::remove_file(&exe).unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "{}", stdout);
+    for d in 1..=15 {
+        assert!(stdout.contains(&format!("y2020 d{:02} p01", d)) || stdout.contains(&format!("y2020 d{:02}     no input", d)), "{}", stdout);
+    }
+    // Without any checked answer (like when no input is found) the test would pass without testing anything
+    assert!(stdout.lines().any(|l| l.ends_with(" ok")), "{}", stdout);
+}